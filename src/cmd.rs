@@ -11,7 +11,15 @@ pub struct Cmd {
 	#[clap(short, long)]
 	pub output: Option<OutputTo<'static>>,
 
-	/// Explain why files are excluded from the sitemap.
+	/// Read the list of files to consider from the given file (`-` means standard input), instead of scanning the `root_dir`, ignoring the configuration file's `file_list_path`.
+	#[clap(short, long)]
+	pub file_list: Option<InputFrom<'static>>,
+
+	/// Read the files to consider from the given tar, tar.gz, or zip archive, instead of scanning the `root_dir`, ignoring the configuration file's `archive_path`.
+	#[clap(short, long)]
+	pub archive: Option<PathBuf>,
+
+	/// Explain why files are excluded from the sitemap, and how their URL-paths are changed by rules.
 	#[clap(short, long)]
 	pub verbose: bool,
 
@@ -34,6 +42,23 @@ impl Cmd {
 			OutputTo::Stdout
 		}
 	}
+
+	pub fn file_list<'a>(&'a self, cfg: &'a crate::config::Config) -> Option<InputFrom<'a>> {
+		if let Some(file_list) = &self.file_list {
+			match file_list {
+				InputFrom::Stdin => Some(InputFrom::Stdin),
+				InputFrom::File(path) => Some(InputFrom::File(Cow::Borrowed(&**path))),
+			}
+		}
+		else {
+			cfg.file_list_path.as_ref().map(|file_list_path| InputFrom::File(Cow::Borrowed(file_list_path.as_path())))
+		}
+	}
+
+	pub fn archive<'a>(&'a self, cfg: &'a crate::config::Config) -> Option<&'a Path> {
+		self.archive.as_deref()
+		.or(cfg.archive_path.as_deref())
+	}
 }
 
 pub enum OutputTo<'p> {
@@ -53,3 +78,21 @@ impl FromStr for OutputTo<'static> {
 		}
 	}
 }
+
+pub enum InputFrom<'p> {
+	Stdin,
+	File(Cow<'p, Path>),
+}
+
+impl FromStr for InputFrom<'static> {
+	type Err = <PathBuf as FromStr>::Err;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if s == "-" {
+			Ok(Self::Stdin)
+		}
+		else {
+			Ok(Self::File(Cow::Owned(PathBuf::from_str(s)?)))
+		}
+	}
+}
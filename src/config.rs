@@ -1,5 +1,4 @@
 use anyhow::Context as _;
-use regex::Regex;
 use serde::Deserialize;
 use std::path::{Path, PathBuf};
 use url::Url;
@@ -10,36 +9,185 @@ pub struct Config {
 	pub root_dir: PathBuf,
 	pub root_url: Url,
 	pub sitemap_path: Option<PathBuf>,
+	pub sitemap_url: Option<Url>,
+	pub html_sitemap_path: Option<PathBuf>,
+	pub html_sitemap_template: Option<PathBuf>,
+	pub file_list_path: Option<PathBuf>,
+	pub archive_path: Option<PathBuf>,
+	pub archive_strip_prefix: Option<PathBuf>,
+	#[serde(default)]
+	pub merge_sitemap_paths: Vec<PathBuf>,
+	pub sitemap_index_path: Option<PathBuf>,
+	pub sitemap_index_url: Option<Url>,
+	#[serde(default, rename = "index_sitemap")]
+	pub index_sitemaps: Vec<IndexSitemap>,
+	#[serde(default)]
+	pub regex_engine: RegexEngine,
+	#[serde(default)]
+	pub replace_mode: ReplaceMode,
+	#[serde(default)]
+	pub match_case_insensitive: bool,
+	pub match_unicode_normalization: Option<UnicodeNormalization>,
+	#[serde(default)]
+	pub normalize_loc: bool,
+	#[serde(default = "Config::default_respect_robots_txt")]
+	pub respect_robots_txt: bool,
+	pub robots_txt_path: Option<PathBuf>,
+	#[serde(default)]
+	pub update_robots_txt: bool,
+	#[serde(default)]
+	pub warn_robots_txt_exclusions: bool,
+	pub max_robots_txt_exclusion_percent: Option<f64>,
+	pub headers_path: Option<PathBuf>,
+	#[serde(default = "Config::default_robots_user_agents")]
+	pub robots_user_agents: Vec<String>,
+	#[serde(default)]
+	pub robots_user_agents_policy: RobotsUserAgentsPolicy,
+	#[serde(default = "Config::default_meta_robots_agents")]
+	pub meta_robots_agents: Vec<String>,
+	#[serde(default)]
+	pub meta_robots_position: MetaRobotsPosition,
+	#[serde(default = "Config::default_html_fallback_encoding", deserialize_with = "deserialize_encoding")]
+	pub html_fallback_encoding: &'static encoding_rs::Encoding,
+	pub max_html_read_bytes: Option<u64>,
+	#[serde(default = "Config::default_error_page_file_names")]
+	pub error_page_file_names: Vec<String>,
+	#[serde(default = "Config::default_error_page_titles")]
+	pub error_page_titles: Vec<String>,
+	#[serde(default = "Config::default_pdf_noindex_key")]
+	pub pdf_noindex_key: String,
+	#[serde(default = "Config::default_pdf_noindex_value")]
+	pub pdf_noindex_value: String,
 	#[serde(rename = "rule")]
 	pub rules: Vec<Rule>,
 }
 
 impl Config {
+	fn default_respect_robots_txt() -> bool {
+		true
+	}
+
+	fn default_robots_user_agents() -> Vec<String> {
+		Vec::from(["*".to_owned()])
+	}
+
+	fn default_meta_robots_agents() -> Vec<String> {
+		Vec::from(["robots".to_owned()])
+	}
+
+	fn default_html_fallback_encoding() -> &'static encoding_rs::Encoding {
+		encoding_rs::UTF_8
+	}
+
+	fn default_error_page_file_names() -> Vec<String> {
+		Vec::from([
+			// HTTP error status codes, like `404.html` or `_500.html`.
+			r"^_?[45][0-9]{2}\.x?html?$".to_owned(),
+			// Generic error and offline fallback pages.
+			r"^_?(error|not[-_]?found|offline|fallback)\.x?html?$".to_owned(),
+		])
+	}
+
+	fn default_pdf_noindex_key() -> String {
+		"Robots".to_owned()
+	}
+
+	fn default_pdf_noindex_value() -> String {
+		"noindex".to_owned()
+	}
+
+	fn default_error_page_titles() -> Vec<String> {
+		Vec::from([
+			// These match the whole title, so that articles about these errors (like `How to fix a page not found error`) aren't excluded.
+			// An HTTP error status code followed by its reason, like `404 Not Found` or `500 - Internal Server Error`, but not just any title that starts with a number, like `500 Days of Summer`.
+			r"^[45][0-9]{2}\s*[-–—:|]?\s*(not found|page not found|error|server error|internal server error|forbidden|access denied|unauthorized|gone|bad request|bad gateway|service unavailable|gateway time-?out)[.!]?$".to_owned(),
+			r"^(page )?not found[.!]?$".to_owned(),
+			r"^(you are |you're )?offline[.!]?$".to_owned(),
+		])
+	}
+
 	pub fn resolve_paths(&mut self, config_file_path: &Path) -> anyhow::Result<()> {
 		let parent =
 			config_file_path.parent()
 			.context("configuration file path doesn't have a parent")?;
 
-		for path in [Some(&mut self.root_dir), self.sitemap_path.as_mut()] {
-		if let Some(path) = path {
+		for path in self.paths_mut() {
 		if !path.is_absolute() {
 			*path = parent.join(&*path);
-		}}}
+		}}
 
 		Ok(())
 	}
+
+	/// The paths in the configuration that may be relative to the configuration file.
+	fn paths_mut(&mut self) -> impl Iterator<Item = &mut PathBuf> {
+		[
+			Some(&mut self.root_dir),
+			self.sitemap_path.as_mut(),
+			self.robots_txt_path.as_mut(),
+			self.headers_path.as_mut(),
+			self.html_sitemap_path.as_mut(),
+			self.html_sitemap_template.as_mut(),
+			self.file_list_path.as_mut(),
+			self.archive_path.as_mut(),
+			self.sitemap_index_path.as_mut(),
+		]
+		.into_iter()
+		.flatten()
+		.chain(&mut self.merge_sitemap_paths)
+		.chain(self.index_sitemaps.iter_mut().filter_map(|sitemap| sitemap.lastmod_path.as_mut()))
+	}
+
+	/// The path to the headers file: either the configured [`Config::headers_path`], or `_headers` in the `root_dir`.
+	pub fn headers_path_or_default(&self) -> PathBuf {
+		match &self.headers_path {
+			Some(path) => path.clone(),
+			None => self.root_dir.join("_headers"),
+		}
+	}
+
+	/// The path to `robots.txt`: either the configured [`Config::robots_txt_path`], or `robots.txt` in the `root_dir`.
+	pub fn robots_txt_path_or_default(&self) -> PathBuf {
+		match &self.robots_txt_path {
+			Some(path) => path.clone(),
+			None => self.root_dir.join("robots.txt"),
+		}
+	}
+}
+
+/// Another sitemap to list in the sitemap index, besides the generated one.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IndexSitemap {
+	pub url: Url,
+	pub lastmod_path: Option<PathBuf>,
+}
+
+/// Deserializes a character encoding from its name, like `shift_jis` or `windows-1252`.
+fn deserialize_encoding<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<&'static encoding_rs::Encoding, D::Error> {
+	let label = String::deserialize(deserializer)?;
+
+	encoding_rs::Encoding::for_label(label.as_bytes())
+	.ok_or_else(|| serde::de::Error::custom(format!("`{label}` is not a known character encoding")))
 }
 
 #[derive(Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Rule {
-	#[serde(with = "serde_regex")]
-	pub r#match: Regex,
+	pub r#match: String,
+	#[serde(default)]
+	pub match_against: MatchSubject,
+	pub regex_engine: Option<RegexEngine>,
 	pub replace: Option<String>,
 	#[serde(default = "Rule::default_replace_limit")]
 	pub replace_limit: usize,
 	pub include: Option<bool>,
+	pub content_type: Option<String>,
 	pub check_html_meta_robots: Option<bool>,
+	pub check_pdf_noindex: Option<bool>,
+	pub front_matter: Option<bool>,
+	pub html_syntax: Option<HtmlSyntax>,
+	pub html_redirect: Option<HtmlRedirect>,
 }
 
 impl Rule {
@@ -47,3 +195,108 @@ impl Rule {
 		0
 	}
 }
+
+/// Which regex library to use for [`Rule::match`].
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegexEngine {
+	/// The `regex` library. It is fast and guarantees linear-time matching, but doesn't support look-around or back-references.
+	#[default]
+	Standard,
+
+	/// The `fancy-regex` library. It supports look-around and back-references, but uses backtracking, which can be slow.
+	Fancy,
+}
+
+/// What to do when more than one rule with a [`Rule::replace`] matches the same file.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplaceMode {
+	/// Only perform the last matching rule's replacement.
+	#[default]
+	Last,
+
+	/// Perform all of the matching rules' replacements, in order, each on the result of the previous one.
+	Chain,
+}
+
+/// A Unicode normalization form, for [`Config::match_unicode_normalization`].
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnicodeNormalization {
+	/// Normalization Form C (canonical composition).
+	Nfc,
+
+	/// Normalization Form D (canonical decomposition).
+	Nfd,
+}
+
+/// What a [`Rule::match`] is matched against.
+#[derive(Clone, Copy, Default, Deserialize, Eq, PartialEq)]
+pub enum MatchSubject {
+	/// The percent-encoded URL-path, like `foo/bar%20baz.html`.
+	#[default]
+	#[serde(rename = "url_path")]
+	Encoded,
+
+	/// The URL-path with percent-encoding decoded, like `foo/bar baz.html`.
+	#[serde(rename = "decoded_path")]
+	Decoded,
+
+	/// The path of the file relative to the `root_dir`, using the platform's path separator, like `foo/bar baz.html` (or `foo\bar baz.html` on Windows).
+	#[serde(rename = "file_path")]
+	File,
+}
+
+/// When to exclude a file that `robots.txt` disallows for some, but not all, of the [`Config::robots_user_agents`].
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RobotsUserAgentsPolicy {
+	/// Exclude the file if any of the user-agents is disallowed.
+	#[default]
+	Any,
+
+	/// Exclude the file only if all of the user-agents are disallowed.
+	All,
+}
+
+/// Where in an HTML document a `<meta name=robots>` element has to be in order to be honored.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetaRobotsPosition {
+	/// It has to be a child of `<head>`, which has to be a child of `<html>`, which has to be at the top of the document.
+	#[default]
+	Strict,
+
+	/// It has to be somewhere inside `<head>`, such as inside a `<noscript>` in `<head>`.
+	HeadAnywhere,
+
+	/// It can be anywhere in the document, including `<body>`.
+	Anywhere,
+}
+
+/// How to parse a file for [`Rule::check_html_meta_robots`].
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HtmlSyntax {
+	/// Parse it as HTML, the way web browsers parse `text/html`.
+	Html,
+
+	/// Parse it as XHTML, with an XML parser, the way web browsers parse `application/xhtml+xml`.
+	Xml,
+}
+
+/// What to do with an HTML page that redirects elsewhere with `<meta http-equiv=refresh>`, for [`Rule::html_redirect`].
+#[derive(Clone, Copy, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HtmlRedirect {
+	/// List the page in the sitemap as usual.
+	#[default]
+	Include,
+
+	/// Leave the page out of the sitemap.
+	Exclude,
+
+	/// List the redirect's target in the sitemap instead of the page, if the target is within the `root_url`. Otherwise, leave the page out.
+	Replace,
+}
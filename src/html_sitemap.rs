@@ -0,0 +1,147 @@
+use anyhow::Context as _;
+use crate::{
+	config::Config,
+	scan::Page,
+};
+use std::{
+	borrow::Cow,
+	collections::BTreeMap,
+	fmt::Write as _,
+	fs,
+};
+
+/// The placeholder in an HTML sitemap template that is replaced with the list of pages.
+const PLACEHOLDER: &str = "{{sitemap}}";
+
+/// The template used if [`Config::html_sitemap_template`] isn't set.
+const DEFAULT_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Sitemap</title>
+</head>
+<body>
+<h1>Sitemap</h1>
+{{sitemap}}
+</body>
+</html>
+"#;
+
+/// Generates a human-readable HTML sitemap, listing the `pages` as a tree of nested lists that follows the folder structure of their URLs.
+pub fn html_sitemap(cfg: &Config, pages: &[Page]) -> anyhow::Result<String> {
+	let template: String = match &cfg.html_sitemap_template {
+		Some(path) => {
+			fs::read_to_string(path)
+			.with_context(|| format!("couldn't read HTML sitemap template `{}`", path.display()))?
+		}
+
+		None => DEFAULT_TEMPLATE.to_owned(),
+	};
+
+	let (before, after) =
+		template.split_once(PLACEHOLDER)
+		.with_context(|| format!("the HTML sitemap template doesn't contain `{PLACEHOLDER}`, which is where the list of pages goes"))?;
+
+	// Build the tree.
+	let mut root = Node::default();
+
+	for page in pages {
+		// The URL-path relative to the `root_url`, without any query or fragment. Every URL in the sitemap starts with the `root_url`, so this is easy.
+		let rel: &str = page.url.as_str().strip_prefix(cfg.root_url.as_str()).unwrap_or_default();
+		let rel: &str = rel.split(['?', '#']).next().unwrap_or_default();
+
+		let mut node = &mut root;
+
+		// A URL ending in `/` (like a folder's `index.html`) belongs to the folder itself, so the empty segment after the last `/` is skipped.
+		for segment in rel.split('/').filter(|segment| !segment.is_empty()) {
+			let segment = percent_encoding::percent_decode_str(segment).decode_utf8_lossy();
+			node = node.children.entry(segment).or_default();
+		}
+
+		// If more than one page ends up at the same spot (which can happen if they have different queries), the first one wins.
+		node.page.get_or_insert(page);
+	}
+
+	// Render it. The home page, if it's included, is at the top of the tree.
+	let mut list = String::new();
+
+	if let Some(page) = root.page {
+		list.push_str("<ul>\n<li>");
+		write_link(&mut list, page, cfg.root_url.as_str());
+		list.push('\n');
+		write_children(&mut list, &root, 1);
+		list.push_str("</li>\n</ul>\n");
+	}
+	else {
+		write_children(&mut list, &root, 0);
+	}
+
+	Ok([before, list.trim_end(), after].concat())
+}
+
+#[derive(Default)]
+struct Node<'a> {
+	page: Option<&'a Page>,
+	/// The folders and pages inside this one, keyed by the percent-decoded last part of their URL-path.
+	children: BTreeMap<Cow<'a, str>, Node<'a>>,
+}
+
+/// Writes a `<ul>` listing the children of `node`, if it has any.
+fn write_children(html: &mut String, node: &Node, depth: usize) {
+	if node.children.is_empty() {
+		return;
+	}
+
+	let indent = "\t".repeat(depth);
+
+	let _ = writeln!(html, "{indent}<ul>");
+
+	for (segment, child) in &node.children {
+		let _ = write!(html, "{indent}<li>");
+
+		// A page is labeled with its title. Folders without a page of their own, and pages without a title, are labeled with the last part of their URL-path.
+		match child.page {
+			Some(page) => write_link(html, page, segment),
+			None => escape(html, segment),
+		}
+
+		if !child.children.is_empty() {
+			html.push('\n');
+			write_children(html, child, depth + 1);
+			html.push_str(&indent);
+		}
+
+		html.push_str("</li>\n");
+	}
+
+	let _ = writeln!(html, "{indent}</ul>");
+}
+
+/// Writes a link to `page`, followed by its description, if any.
+fn write_link(html: &mut String, page: &Page, fallback_label: &str) {
+	html.push_str("<a href=\"");
+	escape(html, page.url.as_str());
+	html.push_str("\">");
+	escape(html, page.title.as_deref().filter(|title| !title.is_empty()).unwrap_or(fallback_label));
+	html.push_str("</a>");
+
+	if let Some(description) = page.description.as_deref().filter(|description| !description.is_empty()) {
+		html.push_str(" <span class=\"description\">");
+		escape(html, description);
+		html.push_str("</span>");
+	}
+}
+
+/// Appends `text` to `html`, escaping characters that are special in HTML text and attribute values.
+fn escape(html: &mut String, text: &str) {
+	for c in text.chars() {
+		match c {
+			'&' => html.push_str("&amp;"),
+			'<' => html.push_str("&lt;"),
+			'>' => html.push_str("&gt;"),
+			'"' => html.push_str("&quot;"),
+			c => html.push(c),
+		}
+	}
+}
@@ -1,7 +1,11 @@
 mod cmd;
 mod config;
+mod html_sitemap;
 mod rules;
 mod scan;
+mod sitemap_index;
+mod update_robots_txt;
+mod url_path;
 
 const MAX_SITEMAP_URLS: usize = 50_000;
 const MAX_SITEMAP_BYTES: usize = 52_428_800;
@@ -40,16 +44,23 @@ fn main() -> anyhow::Result<()> {
 		anyhow::bail!("the configuration needs to have at least one `[[rule]]` with `include = true`");
 	}
 
+	if cfg.update_robots_txt && cfg.robots_txt_path.is_none() && cmd.archive(&cfg).is_some() {
+		anyhow::bail!("`update_robots_txt` can't update the `robots.txt` inside an archive; please set `robots_txt_path` to say which `robots.txt` to update");
+	}
+
+	if !cfg.index_sitemaps.is_empty() && cfg.sitemap_index_path.is_none() {
+		anyhow::bail!("the configuration has `[[index_sitemap]]`s, but no `sitemap_index_path` to write the sitemap index to");
+	}
+
 	let mut sitemap = Vec::<u8>::new();
 	let mut sitemap_writer =
 		sitemap::writer::SiteMapWriter::new(&mut sitemap)
 		.start_urlset()
 		.unwrap();
 
-	self::scan::Scan {
+	let pages = self::scan::Scan {
 		cmd: &cmd,
 		cfg: &cfg,
-		cfg_path: cmd.config_file.as_path(),
 		w: &mut sitemap_writer,
 	}.scan()?;
 
@@ -61,7 +72,9 @@ fn main() -> anyhow::Result<()> {
 		sitemap.len(),
 	);
 
-	match cmd.output(&cfg) {
+	let output = cmd.output(&cfg);
+
+	match &output {
 		OutputTo::Stdout => {
 			let stdout = io::stdout();
 			let mut stdout = stdout.lock();
@@ -69,9 +82,34 @@ fn main() -> anyhow::Result<()> {
 			.and_then(|_| stdout.flush())
 		}
 
-		OutputTo::File(path) => fs::write(&*path, sitemap.as_slice()),
+		OutputTo::File(path) => fs::write(path, sitemap.as_slice()),
 	}
 	.context("couldn't write sitemap file")?;
 
+	if let Some(html_sitemap_path) = &cfg.html_sitemap_path {
+		let html_sitemap = self::html_sitemap::html_sitemap(&cfg, pages.as_slice())?;
+
+		fs::write(html_sitemap_path, html_sitemap)
+		.with_context(|| format!("couldn't write HTML sitemap file `{}`", html_sitemap_path.display()))?;
+	}
+
+	if let Some(sitemap_index_path) = &cfg.sitemap_index_path {
+		let sitemap_url = self::update_robots_txt::sitemap_url(&cfg, &output)?;
+		let sitemap_index = self::sitemap_index::sitemap_index(&cfg, &sitemap_url, pages.as_slice())?;
+
+		fs::write(sitemap_index_path, sitemap_index)
+		.with_context(|| format!("couldn't write sitemap index file `{}`", sitemap_index_path.display()))?;
+	}
+
+	if cfg.update_robots_txt {
+		// If there's a sitemap index, point to that instead, since it lists the sitemap.
+		let sitemap_url = match &cfg.sitemap_index_path {
+			Some(sitemap_index_path) => self::update_robots_txt::sitemap_index_url(&cfg, sitemap_index_path)?,
+			None => self::update_robots_txt::sitemap_url(&cfg, &output)?,
+		};
+
+		self::update_robots_txt::update_robots_txt(cfg.robots_txt_path_or_default().as_path(), &sitemap_url)?;
+	}
+
 	Ok(())
 }
@@ -1,78 +1,389 @@
-use crate::config::{Config, Rule};
+use anyhow::Context as _;
+use crate::config::{Config, HtmlRedirect, HtmlSyntax, MatchSubject, RegexEngine, ReplaceMode, Rule};
 use std::borrow::Cow;
 
 pub struct Rules<'c> {
 	config: &'c Config,
-	regex_set: regex::RegexSet,
+
+	/// The compiled [`Rule::match`] of each rule, in the same order as [`Config::rules`].
+	matchers: Vec<Matcher>,
+
+	/// All of the rules that use [`RegexEngine::Standard`], combined so that they can be matched all at once. There is one set for each [`MatchSubject`] that any such rule is matched against.
+	standard_sets: Vec<StandardSet>,
+
+	/// Indices of the rules that use [`RegexEngine::Fancy`]. These can't be put in a `RegexSet`, so they are matched one at a time.
+	fancy_rules: Vec<usize>,
+
+	needs_content_type: bool,
 }
 
 impl<'c> Rules<'c> {
-	pub fn new(config: &'c Config) -> Self {
-		let regex_set =
-			regex::RegexSet::new(
-				config.rules.iter()
-				.map(|rule| rule.r#match.as_str())
-			)
-			.expect("one or more `match`es in the configuration file are invalid, but this is impossible because they have already been validated");
-
-		Self { config, regex_set }
+	pub fn new(config: &'c Config) -> anyhow::Result<Self> {
+		let mut matchers = Vec::with_capacity(config.rules.len());
+		let mut standard_rules: Vec<(MatchSubject, Vec<usize>)> = Vec::new();
+		let mut fancy_rules = Vec::new();
+
+		for (rule_index, rule) in config.rules.iter().enumerate() {
+			let engine = rule.regex_engine.unwrap_or(config.regex_engine);
+
+			let matcher = match engine {
+				RegexEngine::Standard => {
+					match standard_rules.iter_mut().find(|(subject, _)| *subject == rule.match_against) {
+						Some((_, rules)) => rules.push(rule_index),
+						None => standard_rules.push((rule.match_against, Vec::from([rule_index]))),
+					}
+
+					regex::RegexBuilder::new(rule.r#match.as_str())
+					.case_insensitive(config.match_case_insensitive)
+					.build()
+					.map(Matcher::Standard)
+					.map_err(anyhow::Error::new)
+				}
+
+				RegexEngine::Fancy => {
+					fancy_rules.push(rule_index);
+
+					// `fancy_regex::RegexBuilder` doesn't have a case-insensitivity option, so use the inline flag instead.
+					let pattern: Cow<str> =
+						if config.match_case_insensitive {
+							Cow::Owned(format!("(?i){}", rule.r#match))
+						}
+						else {
+							Cow::Borrowed(rule.r#match.as_str())
+						};
+
+					fancy_regex::Regex::new(&pattern)
+					.map(Matcher::Fancy)
+					.map_err(anyhow::Error::new)
+				}
+			}
+			.with_context(|| format!("rule #{} has an invalid `match` regex, `{}`", rule_index + 1, rule.r#match))?;
+
+			matchers.push(matcher);
+		}
+
+		let standard_sets =
+			standard_rules.into_iter()
+			.map(|(subject, rules)| StandardSet {
+				subject,
+				set:
+					regex::RegexSetBuilder::new(
+						rules.iter()
+						.map(|rule_index| config.rules[*rule_index].r#match.as_str())
+					)
+					.case_insensitive(config.match_case_insensitive)
+					.build()
+					.expect("one or more `match`es in the configuration file are invalid, but this is impossible because they have already been validated"),
+				rules,
+			})
+			.collect();
+
+		let needs_content_type =
+			config.rules.iter()
+			.any(|rule| rule.content_type.is_some());
+
+		Ok(Self {
+			config,
+			matchers,
+			standard_sets,
+			fancy_rules,
+			needs_content_type,
+		})
+	}
+
+	/// Whether any rule has a [`Rule::content_type`] condition. If not, there's no need to sniff the content type of each file.
+	pub fn needs_content_type(&self) -> bool {
+		self.needs_content_type
 	}
 
-	/// Determines what behavior should be used for the file at the given `path`.
+	/// Determines what behavior should be used for the file whose URL-path is `path` and whose path relative to the `root_dir` is `file_path`.
 	///
-	/// The return value is `None` if the rules say to exclude the file from the sitemap, or `Some` if they say to include it.
-	pub fn apply<'p, 's>(&'s self, path: &'p str) -> Option<AppliedRules<'s, 'p>> {
-		let matching_rules = self.regex_set.matches(path);
+	/// `content_type` is the sniffed MIME type of the file. It should be `Some` if [`Rules::needs_content_type`] is true; if it is `None`, rules with a [`Rule::content_type`] condition never apply.
+	///
+	/// If [`Config::match_unicode_normalization`] is set, the rules' `match`es are matched against normalized copies of `path` and `file_path`. Replacements are performed on the normalized `path` too, so the resulting URL-path is normalized if any rule replaced it, even if [`Config::normalize_loc`] isn't set.
+	///
+	/// The return value is `None` if the rules say to exclude the file from the sitemap, or `Some` if they say to include it. It is an error if a [`RegexEngine::Fancy`] regex fails to run, such as by exceeding its backtracking limit.
+	pub fn apply<'p, 's>(&'s self, path: &'p str, file_path: &str, content_type: Option<&str>) -> anyhow::Result<Option<AppliedRules<'s, 'p>>> {
+		use crate::url_path;
+
+		let normalized_path: Cow<'p, str> = match self.config.match_unicode_normalization {
+			Some(form) => url_path::normalize(path, form),
+			None => Cow::Borrowed(path),
+		};
+
+		let normalize = |subject: Cow<'_, str>| -> String {
+			match self.config.match_unicode_normalization {
+				Some(form) => url_path::normalize_str(&subject, form).into_owned(),
+				None => subject.into_owned(),
+			}
+		};
+
+		let decoded_path: String = normalize(url_path::decode(path));
+		let file_path: String = normalize(Cow::Borrowed(file_path));
+
+		let subject = |subject: MatchSubject| -> &str {
+			match subject {
+				MatchSubject::Encoded => &normalized_path,
+				MatchSubject::Decoded => &decoded_path,
+				MatchSubject::File => &file_path,
+			}
+		};
+
+		let mut matching_rules: Vec<usize> = Vec::new();
 
-		if !matching_rules.matched_any() {
-			return None;
+		for standard_set in &self.standard_sets {
+			matching_rules.extend(
+				standard_set.set.matches(subject(standard_set.subject))
+				.into_iter()
+				.map(|set_index| standard_set.rules[set_index])
+			);
 		}
 
+		for rule_index in &self.fancy_rules {
+			if self.matchers[*rule_index].is_match(subject(self.config.rules[*rule_index].match_against))? {
+				matching_rules.push(*rule_index);
+			}
+		}
+
+		if matching_rules.is_empty() {
+			return Ok(None);
+		}
+
+		// Rules are applied in the order they appear in the configuration file, regardless of which regex engine they use or what they're matched against.
+		matching_rules.sort_unstable();
+
 		let mut include = false;
 		let mut applied = AppliedRules {
-			replacing_rule: None,
-			path: Cow::Borrowed(path),
+			replacements: Vec::new(),
+			path:
+				if self.config.normalize_loc {
+					normalized_path.clone()
+				}
+				else {
+					Cow::Borrowed(path)
+				},
 			check_html_meta_robots: false,
+			check_pdf_noindex: false,
+			front_matter: false,
+			html_syntax: None,
+			html_redirect: HtmlRedirect::default(),
 		};
-		let mut replace: Option<(&'c Rule, &'c str)> = None;
+		let mut replacing_rules: Vec<usize> = Vec::new();
 
-		for matching_rule in matching_rules {
-			// `matching_rule` is currently an index into the `Config::rules` array. Look it up.
-			let matching_rule = &self.config.rules[matching_rule];
+		for matching_rule_index in matching_rules {
+			// `matching_rule_index` is an index into the `Config::rules` array. Look it up.
+			let matching_rule = &self.config.rules[matching_rule_index];
+
+			// Skip the rule if it only applies to some other content type.
+			if let Some(rule_content_type) = &matching_rule.content_type {
+			if !content_type.is_some_and(|content_type| content_type_matches(rule_content_type, content_type)) {
+				continue;
+			}}
 
 			include = matching_rule.include.unwrap_or(include);
 
-			if let Some(matching_replace) = &matching_rule.replace {
-				replace = Some((matching_rule, matching_replace.as_str()));
+			if matching_rule.replace.is_some() {
+				if let ReplaceMode::Last = self.config.replace_mode {
+					replacing_rules.clear();
+				}
+
+				replacing_rules.push(matching_rule_index);
 			}
 
 			if let Some(flag) = matching_rule.check_html_meta_robots {
 				applied.check_html_meta_robots = flag;
 			}
+
+			if let Some(flag) = matching_rule.check_pdf_noindex {
+				applied.check_pdf_noindex = flag;
+			}
+
+			if let Some(flag) = matching_rule.front_matter {
+				applied.front_matter = flag;
+			}
+
+			if let Some(syntax) = matching_rule.html_syntax {
+				applied.html_syntax = Some(syntax);
+			}
+
+			if let Some(html_redirect) = matching_rule.html_redirect {
+				applied.html_redirect = html_redirect;
+			}
 		}
 
 		if !include {
-			return None;
+			return Ok(None);
+		}
+
+		// Replacements are performed on the normalized URL-path, since that's what the rules' `match`es matched. Otherwise, a `match` written in one normalization form could match a file name in another, and then its replacement would find nothing to replace.
+		if !replacing_rules.is_empty() {
+			applied.path = normalized_path;
 		}
 
-		if let Some((replacing_rule, replace)) = replace {
-			applied.replacing_rule = Some(replacing_rule);
-			applied.path =
-				replacing_rule.r#match
-				.replacen(path, replacing_rule.replace_limit, replace);
+		// Perform replacements. In `ReplaceMode::Last`, there's at most one; in `ReplaceMode::Chain`, each one operates on the result of the previous one.
+		for replacing_rule_index in replacing_rules {
+			let replacing_rule = &self.config.rules[replacing_rule_index];
+			let replace = replacing_rule.replace.as_deref().expect("`replacing_rules` contains a rule without a `replace`");
+
+			let matcher = &self.matchers[replacing_rule_index];
+
+			// The replacement is performed on the same form of the path that the rule's `match` was matched against, then converted back into a URL-path. If the replacement didn't change anything, the result is `None`, and there's nothing to update.
+			let replaced: Option<String> = match replacing_rule.match_against {
+				MatchSubject::Encoded => {
+					matcher.replacen(&applied.path, replacing_rule.replace_limit, replace)?
+					.into_owned_if_changed()
+				}
+
+				MatchSubject::Decoded => {
+					matcher.replacen(&url_path::decode(&applied.path), replacing_rule.replace_limit, replace)?
+					.into_owned_if_changed()
+					.map(|replaced| url_path::encode(&replaced))
+				}
+
+				MatchSubject::File => {
+					matcher.replacen(&url_path::decode_to_file_path(&applied.path), replacing_rule.replace_limit, replace)?
+					.into_owned_if_changed()
+					.map(|replaced| url_path::encode_file_path(&replaced))
+				}
+			};
+
+			if let Some(replaced) = replaced {
+				applied.path = Cow::Owned(replaced);
+			}
+
+			applied.replacements.push(Replacement {
+				rule_index: replacing_rule_index,
+				rule: replacing_rule,
+				path: applied.path.clone().into_owned(),
+			});
+		}
+
+		Ok(Some(applied))
+	}
+}
+
+/// The rules that use [`RegexEngine::Standard`] and are matched against a particular [`MatchSubject`].
+struct StandardSet {
+	subject: MatchSubject,
+	set: regex::RegexSet,
+
+	/// For each regex in `set`, the index of the rule it came from.
+	rules: Vec<usize>,
+}
+
+trait CowExt {
+	/// Returns the owned string if this is [`Cow::Owned`], or `None` if it's [`Cow::Borrowed`]. Replacement methods return `Cow::Borrowed` if nothing was replaced, so this returns `None` in that case.
+	fn into_owned_if_changed(self) -> Option<String>;
+}
+
+impl CowExt for Cow<'_, str> {
+	fn into_owned_if_changed(self) -> Option<String> {
+		match self {
+			Cow::Owned(changed) => Some(changed),
+			Cow::Borrowed(_) => None,
+		}
+	}
+}
+
+/// A compiled [`Rule::match`].
+enum Matcher {
+	Standard(regex::Regex),
+	Fancy(fancy_regex::Regex),
+}
+
+impl Matcher {
+	fn is_match(&self, text: &str) -> anyhow::Result<bool> {
+		match self {
+			Self::Standard(regex) => Ok(regex.is_match(text)),
+
+			Self::Fancy(regex) => {
+				regex.is_match(text)
+				.with_context(|| format!("couldn't match the regex `{regex}` against `{text}`"))
+			}
+		}
+	}
+
+	fn replacen<'t>(&self, text: &'t str, limit: usize, replace: &str) -> anyhow::Result<Cow<'t, str>> {
+		match self {
+			Self::Standard(regex) => Ok(regex.replacen(text, limit, replace)),
+
+			// `fancy_regex::Regex::replacen` panics if the regex fails to run, so do the replacement ourselves instead.
+			Self::Fancy(regex) => {
+				let mut replaced = String::new();
+				let mut last_match_end = 0;
+				let mut matched = false;
+
+				for (count, captures) in regex.captures_iter(text).enumerate() {
+					if limit > 0 && count >= limit {
+						break;
+					}
+
+					let captures =
+						captures
+						.with_context(|| format!("couldn't match the regex `{regex}` against `{text}`"))?;
+
+					let whole_match = captures.get(0).expect("capture group 0 is missing");
+
+					replaced.push_str(&text[last_match_end..whole_match.start()]);
+					captures.expand(replace, &mut replaced);
+					last_match_end = whole_match.end();
+					matched = true;
+				}
+
+				if !matched {
+					return Ok(Cow::Borrowed(text));
+				}
+
+				replaced.push_str(&text[last_match_end..]);
+				Ok(Cow::Owned(replaced))
+			}
+		}
+	}
+}
+
+/// Checks whether the MIME type `content_type` matches the `pattern` given in a rule. The pattern is either a full MIME type like `text/html` or a wildcard like `image/*`. Comparison is case-insensitive.
+fn content_type_matches(pattern: &str, content_type: &str) -> bool {
+	match pattern.strip_suffix("/*") {
+		Some(pattern_type) => {
+			content_type.split_once('/')
+			.is_some_and(|(r#type, _)| r#type.eq_ignore_ascii_case(pattern_type))
 		}
 
-		Some(applied)
+		None => pattern.eq_ignore_ascii_case(content_type),
 	}
 }
 
 pub struct AppliedRules<'c, 'p> {
-	/// The rule whose [`Rule::replace`] has been applied. `None` if none of the matching rules have a `replace`.
-	pub replacing_rule: Option<&'c Rule>,
+	/// The [`Rule::replace`]ments that have been performed, in order. Empty if none of the matching rules have a `replace`.
+	pub replacements: Vec<Replacement<'c>>,
 
-	/// The new URL-path for the sitemap entry. Will be [`Cow::Owned`] if the path has been subjected to replacement, or [`Cow::Borrowed`] if not.
+	/// The new URL-path for the sitemap entry. Will be [`Cow::Owned`] if the path has been subjected to replacement or normalization, or [`Cow::Borrowed`] if not.
 	pub path: Cow<'p, str>,
 
 	/// Whether to try to parse the file as HTML and look for `<meta name=robots>`.
 	pub check_html_meta_robots: bool,
+
+	/// Whether to check the file's PDF document metadata for [`Config::pdf_noindex_key`], if it's a PDF.
+	pub check_pdf_noindex: bool,
+
+	/// Whether to read the file's front matter, for `draft`, `sitemap`, `lastmod`, and `url` fields.
+	pub front_matter: bool,
+
+	/// How to parse the file as HTML, if a rule says. Otherwise, it's decided by the file name extension.
+	pub html_syntax: Option<HtmlSyntax>,
+
+	/// What to do if the file is an HTML page that redirects elsewhere.
+	pub html_redirect: HtmlRedirect,
+}
+
+/// A step in rewriting a URL-path, as recorded in [`AppliedRules::replacements`].
+pub struct Replacement<'c> {
+	/// The index of the rule in [`Config::rules`].
+	pub rule_index: usize,
+
+	/// The rule whose [`Rule::replace`] was performed.
+	pub rule: &'c Rule,
+
+	/// The URL-path after this replacement. This is the same as the URL-path before it, if the rule's `match` didn't match anything.
+	pub path: String,
 }
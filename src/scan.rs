@@ -1,71 +1,224 @@
 use anyhow::Context as _;
 use crate::{
-	config::Config,
-	cmd::Cmd,
+	config::{Config, HtmlRedirect, HtmlSyntax},
+	cmd::{Cmd, InputFrom},
 	MAX_SITEMAP_URLS,
 	rules::Rules,
 };
+use self::{
+	archive::Archive,
+	error_pages::ErrorPages,
+	file_list::FileListEntry,
+	front_matter::FrontMatter,
+	headers_file::HeadersFile,
+	robots::Robots,
+};
 use std::{
 	cmp::Ordering,
 	fmt::Display,
 	fs::{self, File},
-	io::{self, Write},
+	io::{self, Read, Seek, Write},
+	mem,
 	path::{Path, PathBuf},
+	time::SystemTime,
 };
 use url::Url;
 
+mod archive;
 mod check_html_meta;
+mod check_pdf_meta;
+mod error_pages;
+mod file_list;
+mod front_matter;
+mod headers_file;
+mod merge_sitemap;
+mod robots;
+mod robots_directives;
+mod sniff_content_type;
+mod sniff_html_encoding;
 
 pub struct Scan<'c, W: Write> {
 	pub cmd: &'c Cmd,
 	pub cfg: &'c Config,
-	pub cfg_path: &'c Path,
 	pub w: &'c mut sitemap::writer::UrlSetWriter<W>,
 }
 
+/// A page that was included in the sitemap, along with what the HTML sitemap and sitemap index need to know about it.
+pub struct Page {
+	pub url: Url,
+
+	/// The page's `<title>`, if it's an HTML page that has one.
+	pub title: Option<String>,
+
+	/// The page's `<meta name=description>`, if it's an HTML page that has one.
+	pub description: Option<String>,
+
+	/// The page's `<lastmod>` in the sitemap, if any.
+	pub lastmod: Option<chrono::DateTime<chrono::FixedOffset>>,
+}
+
 impl<'c, W: Write> Scan<'c, W> {
-	pub fn scan(self) -> anyhow::Result<()> {
+	/// Scans the `root_dir`, writes the sitemap entries, and returns the pages that were included.
+	pub fn scan(self) -> anyhow::Result<Vec<Page>> {
 		anyhow::ensure!(
 			!self.cfg.root_url.cannot_be_a_base(),
 			"the configured `root_url`, `{}`, is unusable as it cannot serve as a base URL",
 			self.cfg.root_url,
 		);
 
-		let robots_path: PathBuf = self.cfg.root_dir.join("robots.txt");
+		let file_list = self.cmd.file_list(self.cfg);
 
-		let robot: Option<texting_robots::Robot> = match fs::read(robots_path.as_path()) {
-			Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+		let archive: Option<Archive> = match self.cmd.archive(self.cfg) {
+			Some(archive_path) => {
+				anyhow::ensure!(
+					file_list.is_none(),
+					"a file list and an archive can't both be used at the same time",
+				);
 
-			Err(error) => return Err(
-				anyhow::Error::new(error)
-				.context("couldn't read `robots.txt`")
-			),
+				Some(Archive::open(archive_path, self.cfg.archive_strip_prefix.as_deref())?)
+			}
 
-			Ok(robots_bytes) => Some(
-				texting_robots::Robot::new("*", robots_bytes.as_slice())
-				.context("`robots.txt` is invalid")?
-			),
+			None => None,
+		};
+
+		// When scanning an archive, `robots.txt` and the headers file are in the archive, unless the configuration file names specific ones. Look for both in one pass over the archive.
+		let [archive_robots_txt, archive_headers] = match &archive {
+			Some(archive) => archive.read_files([
+				(self.cfg.respect_robots_txt && self.cfg.robots_txt_path.is_none()).then_some(Path::new("robots.txt")),
+				self.cfg.headers_path.is_none().then_some(Path::new("_headers")),
+			])?,
+
+			None => [None, None],
 		};
 
+		let robots_path: PathBuf = self.cfg.robots_txt_path_or_default();
+
+		let robots: Option<Robots> =
+			if !self.cfg.respect_robots_txt {
+				None
+			}
+			else if let (Some(_), None) = (&archive, &self.cfg.robots_txt_path) {
+				match archive_robots_txt {
+					Some(robots_bytes) => Some(Robots::new(self.cfg, robots_bytes)?),
+					None => None,
+				}
+			}
+			else {
+				match fs::read(robots_path.as_path()) {
+					// It's fine if there's no `robots.txt` in the `root_dir`, but if the configuration file names a specific `robots.txt`, it had better exist.
+					Err(error) if error.kind() == io::ErrorKind::NotFound && self.cfg.robots_txt_path.is_none() => None,
+
+					Err(error) => return Err(
+						anyhow::Error::new(error)
+						.context(format!("couldn't read `robots.txt` at `{}`", robots_path.display()))
+					),
+
+					Ok(robots_bytes) => Some(Robots::new(self.cfg, robots_bytes)?),
+				}
+			};
+
+		let headers_path: PathBuf = self.cfg.headers_path_or_default();
+
+		let headers_text: Option<String> =
+			if let (Some(archive), None) = (&archive, &self.cfg.headers_path) {
+				match archive_headers {
+					Some(headers_bytes) => Some(
+						String::from_utf8(headers_bytes)
+						.with_context(|| format!("headers file `_headers` in archive `{}` is not valid UTF-8", archive.path().display()))?
+					),
+
+					None => None,
+				}
+			}
+			else {
+				match fs::read_to_string(headers_path.as_path()) {
+					// It's fine if there's no `_headers` in the `root_dir`, but if the configuration file names a specific headers file, it had better exist.
+					Err(error) if error.kind() == io::ErrorKind::NotFound && self.cfg.headers_path.is_none() => None,
+
+					Err(error) => return Err(
+						anyhow::Error::new(error)
+						.context(format!("couldn't read headers file `{}`", headers_path.display()))
+					),
+
+					Ok(text) => Some(text),
+				}
+			};
+
+		let headers_file: Option<HeadersFile> = headers_text.map(|text| HeadersFile::parse(text.as_str()));
+
+		if self.cmd.verbose {
+		if let Some(headers_file) = &headers_file {
+			for (line_number, line) in &headers_file.unrecognized_lines {
+				eprintln!("Warning: line {line_number} of headers file `{}`, `{line}`, isn't recognized, so it was skipped.", headers_path.display());
+			}
+		}}
+
 		let root_dir_url: Url =
 			Url::from_directory_path(self.cfg.root_dir.as_path())
 			.map_err(|()| anyhow::anyhow!("`{}` is not a valid `root_dir`", self.cfg.root_dir.display()))?;
 
-		let rules = Rules::new(self.cfg);
+		let rules = Rules::new(self.cfg)?;
+		let error_pages = ErrorPages::new(self.cfg)?;
 
 		let mut scanner = Scanner {
 			s: self,
 			root_dir_url: &root_dir_url,
-			robot: &robot,
+			robots: &robots,
+			headers_file: &headers_file,
 			rules: &rules,
+			error_pages: &error_pages,
+			now: chrono::Utc::now(),
 			urls: Vec::new(),
+			rules_included_count: 0,
+			robots_excluded_count: 0,
 		};
-		scanner.scan_dir(scanner.s.cfg.root_dir.as_path())?;
+		if let Some(archive) = &archive {
+			scanner.scan_archive(archive)?;
+		}
+		else if let Some(file_list) = file_list {
+			let text: String = match &file_list {
+				InputFrom::Stdin => {
+					io::read_to_string(io::stdin())
+					.context("couldn't read file list from standard input")?
+				}
+
+				InputFrom::File(path) => {
+					fs::read_to_string(path)
+					.with_context(|| format!("couldn't read file list `{}`", path.display()))?
+				}
+			};
+
+			let entries =
+				self::file_list::parse(text.as_str())
+				.context("invalid file list")?;
+
+			scanner.scan_file_list(entries)?;
+		}
+		else {
+			scanner.scan_dir(scanner.s.cfg.root_dir.as_path())?;
+		}
+
+		scanner.check_robots_excluded_count()?;
+
+		// Merge in the entries of existing sitemaps.
+		for path in &scanner.s.cfg.merge_sitemap_paths {
+			let entries = self::merge_sitemap::read_sitemap(path.as_path(), &scanner.s.cfg.root_url)?;
+
+			if scanner.s.cmd.verbose {
+				eprintln!("Merging {} URLs from sitemap `{}`.", entries.len(), path.display());
+			}
+
+			scanner.urls.extend(entries.into_iter().map(|entry| ScannedUrl {
+				entry,
+				title: None,
+				description: None,
+			}));
+		}
 
 		scanner.urls.sort_by(|a, b| {
 			use sitemap::structs::Location;
 
-			match (&a.loc, &b.loc) {
+			match (&a.entry.loc, &b.entry.loc) {
 				(Location::None, Location::None)
 				| (Location::ParseErr(_), Location::ParseErr(_))
 				=> Ordering::Equal,
@@ -83,21 +236,89 @@ impl<'c, W: Write> Scan<'c, W> {
 			}
 		});
 
+		// Redirects replaced by their targets, and merged sitemaps, may have produced duplicate URLs. Keep only one of each, with the latest modification time.
+		scanner.urls.dedup_by(|dup, kept| {
+			use sitemap::structs::{ChangeFreq, LastMod, Priority};
+
+			if dup.entry.loc.get_url().is_none() || dup.entry.loc.get_url() != kept.entry.loc.get_url() {
+				return false;
+			}
+
+			if let Some(dup_time) = dup.entry.lastmod.get_time() {
+			if !matches!(kept.entry.lastmod.get_time(), Some(kept_time) if kept_time >= dup_time) {
+				kept.entry.lastmod = LastMod::DateTime(dup_time);
+			}}
+
+			if matches!(kept.entry.changefreq, ChangeFreq::None) {
+				kept.entry.changefreq = mem::replace(&mut dup.entry.changefreq, ChangeFreq::None);
+			}
+
+			if matches!(kept.entry.priority, Priority::None) {
+				kept.entry.priority = mem::replace(&mut dup.entry.priority, Priority::None);
+			}
+
+			if kept.title.is_none() {
+				kept.title = dup.title.take();
+			}
+
+			if kept.description.is_none() {
+				kept.description = dup.description.take();
+			}
+
+			true
+		});
+
+		let mut pages: Vec<Page> = Vec::with_capacity(scanner.urls.len());
+
+		// Make sure not to exceed 50k URLs. This is checked only now, after duplicates have been removed.
+		anyhow::ensure!(
+			scanner.urls.len() <= MAX_SITEMAP_URLS,
+			"more than {MAX_SITEMAP_URLS} URLs are to be included in the sitemap, which is not allowed by the sitemaps protocol; please divide the files into multiple sitemaps and join them together in a sitemap index",
+		);
+
 		for url in scanner.urls {
-			scanner.s.w.url(url)
+			if let Some(loc) = url.entry.loc.get_url() {
+				pages.push(Page {
+					url: loc,
+					title: url.title,
+					description: url.description,
+					lastmod: url.entry.lastmod.get_time(),
+				});
+			}
+
+			scanner.s.w.url(url.entry)
 			.context("couldn't write sitemap entry")?;
 		}
 
-		Ok(())
+		Ok(pages)
 	}
 }
 
 struct Scanner<'a, W: Write> {
-	robot: &'a Option<texting_robots::Robot>,
+	robots: &'a Option<Robots<'a>>,
+	headers_file: &'a Option<HeadersFile>,
 	root_dir_url: &'a Url,
 	rules: &'a Rules<'a>,
+	error_pages: &'a ErrorPages,
 	s: Scan<'a, W>,
-	urls: Vec<sitemap::structs::UrlEntry>,
+
+	/// The time the scan started, for checking `unavailable_after` robots directives.
+	now: chrono::DateTime<chrono::Utc>,
+
+	urls: Vec<ScannedUrl>,
+
+	/// How many files the rules say to include.
+	rules_included_count: usize,
+
+	/// How many of the files counted in `rules_included_count` were then excluded by `robots.txt`.
+	robots_excluded_count: usize,
+}
+
+/// A sitemap entry, along with the page's title and description (if any) for the HTML sitemap.
+struct ScannedUrl {
+	entry: sitemap::structs::UrlEntry,
+	title: Option<String>,
+	description: Option<String>,
 }
 
 impl<'a, W: Write> Scanner<'a, W> {
@@ -138,107 +359,481 @@ impl<'a, W: Write> Scanner<'a, W> {
 				continue;
 			}
 
-			// An absolute `file:` URL.
-			let file_url =
-				Url::from_file_path(dent_path.as_path())
-				.map_err(|()| anyhow::format_err!("path `{}` couldn't be converted into a URL", dent_path.display()))?;
+			self.scan_file(dent_path.as_path(), Some(&mut fd), md.modified().ok().map(file_time))?;
+		}
 
-			// A relative URL (just the path).
-			let url_rel =
-				self.root_dir_url.make_relative(&file_url)
-				.unwrap_or_else(|| panic!("the URL `{file_url}` could not be made relative to the URL `{}`", self.root_dir_url));
+		Ok(())
+	}
 
-			let explain_exclude = |reason: &dyn Display| -> () {
-				if self.s.cmd.verbose {
-					eprintln!("Excluding `{url_rel}` (at file path `{}`). {reason}", dent_path.display());
+	/// Considers each of the files in an archive, instead of scanning the `root_dir`. Each file is treated as if the archive had been extracted into the `root_dir`.
+	fn scan_archive(&mut self, archive: &Archive) -> anyhow::Result<()> {
+		let verbose: bool = self.s.cmd.verbose;
+		let root_dir: PathBuf = self.s.cfg.root_dir.clone();
+
+		archive.for_each_entry(
+			|file| {
+				if file.path.as_os_str().is_empty() {
+					return Ok(true);
 				}
-			};
 
-			// A relative URL, with replacements applied.
-			let applied_rules = match self.rules.apply(url_rel.as_str()) {
-				Some(ok) => ok,
-				None => {
-					explain_exclude(&"The rules don't say to include it.");
-					continue
+				self.scan_file(root_dir.join(&file.path).as_path(), Some(file.content), file.modified)?;
+				Ok(true)
+			},
+
+			|other| {
+				if verbose && !other.is_dir {
+					eprintln!("Excluding `{}` in the archive. It is not a regular file.", other.path.display());
 				}
-			};
+			},
+		)
+	}
+
+	/// Considers each of the files in a file list, instead of scanning the `root_dir`. Files that exist in the `root_dir` are checked as usual; files that don't are judged by their path alone.
+	fn scan_file_list(&mut self, entries: Vec<FileListEntry>) -> anyhow::Result<()> {
+		use std::path::Component;
+
+		for FileListEntry { path: entry_path, lastmod } in entries {
+			// Resolve the path relative to the `root_dir`, and make sure it doesn't lead out of there.
+			let mut path: PathBuf = self.s.cfg.root_dir.clone();
+
+			if entry_path.is_absolute() {
+				anyhow::ensure!(
+					entry_path.starts_with(self.s.cfg.root_dir.as_path()),
+					"`{}`, in the file list, is not inside the `root_dir`, `{}`",
+					entry_path.display(),
+					self.s.cfg.root_dir.display(),
+				);
+			}
+
+			let entry_path_rel: &Path = entry_path.strip_prefix(self.s.cfg.root_dir.as_path()).unwrap_or(entry_path.as_path());
+
+			for component in entry_path_rel.components() {
+				match component {
+					Component::Normal(name) => path.push(name),
+					Component::CurDir => {}
+					Component::ParentDir | Component::RootDir | Component::Prefix(_) => anyhow::bail!(
+						"`{}`, in the file list, is not a path inside the `root_dir`",
+						entry_path.display(),
+					),
+				}
+			}
+
+			anyhow::ensure!(
+				path != self.s.cfg.root_dir,
+				"the file list contains an empty path",
+			);
+
+			match File::open(path.as_path()) {
+				Ok(mut fd) => {
+					let md =
+						fd.metadata()
+						.with_context(|| format!("couldn't get file system metadata for file `{}`", path.display()))?;
+
+					if !md.is_file() {
+						if self.s.cmd.verbose {
+							eprintln!("Excluding file `{}`. It is not a regular file.", path.display());
+						}
+
+						continue;
+					}
+
+					self.scan_file(path.as_path(), Some(&mut fd), lastmod.or_else(|| md.modified().ok().map(file_time)))?;
+				}
+
+				Err(error) if error.kind() == io::ErrorKind::NotFound => {
+					if self.s.cmd.verbose {
+						eprintln!("File `{}` doesn't exist, so it will be judged by its path alone.", path.display());
+					}
+
+					self.scan_file(path.as_path(), None::<File>, lastmod)?;
+				}
+
+				Err(error) => return Err(
+					anyhow::Error::new(error)
+					.context(format!("couldn't open file `{}`", path.display()))
+				),
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Decides whether to include a file in the sitemap, and if so, adds it to [`Scanner::urls`].
+	///
+	/// `path` is the file's absolute path, which must be inside the `root_dir`. The file doesn't have to actually exist there, though: if its `content` isn't available, the file is judged by its path alone, and any rule options that need to look inside the file are skipped. `modified` is the file's last modification time, if known.
+	fn scan_file<F: Read + Seek>(&mut self, path: &Path, mut content: Option<F>, modified: Option<chrono::DateTime<chrono::Utc>>) -> anyhow::Result<()> {
+		// An absolute `file:` URL.
+		let file_url =
+			Url::from_file_path(path)
+			.map_err(|()| anyhow::format_err!("path `{}` couldn't be converted into a URL", path.display()))?;
+
+		// A relative URL (just the path).
+		let url_rel =
+			self.root_dir_url.make_relative(&file_url)
+			.unwrap_or_else(|| panic!("the URL `{file_url}` could not be made relative to the URL `{}`", self.root_dir_url));
+
+		// The path of the file relative to the `root_dir`.
+		let file_rel: &Path =
+			path.strip_prefix(self.s.cfg.root_dir.as_path())
+			.unwrap_or_else(|_| panic!("the path `{}` is not inside `{}`", path.display(), self.s.cfg.root_dir.display()));
+
+		let explain_exclude = |reason: &dyn Display| -> () {
+			if self.s.cmd.verbose {
+				eprintln!("Excluding `{url_rel}` (at file path `{}`). {reason}", path.display());
+			}
+		};
+
+		// Sniff the content type, if any rules care about it.
+		let content_type: Option<&str> = match &mut content {
+			Some(fd) if self.rules.needs_content_type() => Some(
+				self::sniff_content_type::sniff_content_type(fd)
+				.with_context(|| format!("couldn't read file `{}`", path.display()))?
+			),
+
+			_ => None,
+		};
+
+		// A relative URL, with replacements applied.
+		let applied_rules = match self.rules.apply(url_rel.as_str(), &file_rel.to_string_lossy(), content_type)? {
+			Some(ok) => ok,
+			None => {
+				explain_exclude(&"The rules don't say to include it.");
+				return Ok(());
+			}
+		};
 
-			// The absolute URL of the file, as it will appear on the web.
-			let web_url =
-				self.s.cfg.root_url.join(&*applied_rules.path)
-				.with_context(|| format!(
-					"applying configured replacements to `{url_rel}` yielded `{}`, which is not a valid relative URL",
-					applied_rules.path,
-				))?;
+		if self.s.cmd.verbose {
+			let mut prev_path: &str = url_rel.as_str();
+
+			for replacement in &applied_rules.replacements {
+				if replacement.path == prev_path {
+					eprintln!("Rule #{} (`{}`) matched `{url_rel}` but didn't change its URL-path, `{prev_path}`.", replacement.rule_index + 1, replacement.rule.r#match);
+				}
+				else {
+					eprintln!("Rule #{} (`{}`) changed the URL-path of `{url_rel}` from `{prev_path}` to `{}`.", replacement.rule_index + 1, replacement.rule.r#match, replacement.path);
+				}
+
+				prev_path = replacement.path.as_str();
+			}
+		}
+
+		// The absolute URL of the file, as it will appear on the web.
+		let mut web_url =
+			self.s.cfg.root_url.join(&applied_rules.path)
+			.with_context(|| format!(
+				"applying configured replacements to `{url_rel}` yielded `{}`, which is not a valid relative URL",
+				applied_rules.path,
+			))?;
+
+		anyhow::ensure!(
+			web_url.as_str().starts_with(self.s.cfg.root_url.as_str()),
+			"applying replacements to the path `{}` resulted in the URL `{web_url}`, which does not start with the configured `root_url`, `{}`, in violation of the sitemaps protocol",
+			path.display(),
+			self.s.cfg.root_url,
+		);
+
+		// Read the file's front matter, if a rule says to.
+		let front_matter: FrontMatter = match &mut content {
+			Some(fd) if applied_rules.front_matter => {
+				let front_matter =
+					FrontMatter::read(io::BufReader::new(&mut *fd))
+					.with_context(|| format!("couldn't read front matter of file `{}`", path.display()))?;
+
+				fd.rewind()
+				.with_context(|| format!("couldn't read file `{}`", path.display()))?;
+
+				front_matter
+			}
+
+			_ => FrontMatter::default(),
+		};
+
+		if front_matter.draft {
+			explain_exclude(&"It's a draft (its front matter has `draft: true`).");
+			return Ok(());
+		}
+
+		if front_matter.sitemap_disabled {
+			explain_exclude(&"Its front matter says to leave it out of the sitemap.");
+			return Ok(());
+		}
+
+		// The front matter's `url` replaces the URL figured out by the rules. Like the rules' replacements, it's relative to the `root_url`, even if it starts with a slash.
+		if let Some(url) = &front_matter.url {
+			let new_web_url =
+				self.s.cfg.root_url.join(url.trim_start_matches('/'))
+				.with_context(|| format!("the `url` in the front matter of `{}`, `{url}`, is not a valid relative URL", path.display()))?;
 
 			anyhow::ensure!(
-				web_url.as_str().starts_with(self.s.cfg.root_url.as_str()),
-				"applying replacements to the path `{}` resulted in the URL `{web_url}`, which does not start with the configured `root_url`, `{}`, in violation of the sitemaps protocol",
-				dent_path.display(),
+				new_web_url.as_str().starts_with(self.s.cfg.root_url.as_str()),
+				"the `url` in the front matter of `{}` resulted in the URL `{new_web_url}`, which does not start with the configured `root_url`, `{}`, in violation of the sitemaps protocol",
+				path.display(),
 				self.s.cfg.root_url,
 			);
 
-			// Check if this file is excluded by the robots file. Note that the robots protocol expects a leading slash, and `Url::make_relative` makes a string *without* a leading slash, so we'll have to copy the whole URL-path into a new string with such a slash.
-			if let Some(robot) = self.robot {
-			if !robot.allowed(format!("/{url_rel}").as_str()) {
-				explain_exclude(&format_args!("`robots.txt` says to exclude it."));
-				continue;
+			if self.s.cmd.verbose {
+				eprintln!("The front matter of `{url_rel}` changed its URL from `{web_url}` to `{new_web_url}`.");
+			}
+
+			web_url = new_web_url;
+		}
+
+		// Check if this file is an error page, by its name.
+		if let Some(error_page) = self.error_pages.check_file_name(&path.file_name().unwrap_or_default().to_string_lossy()) {
+			explain_exclude(&error_page);
+			return Ok(());
+		}
+
+		self.rules_included_count += 1;
+
+		// Check if this file is excluded by the robots file. Note that the robots protocol expects a leading slash, and `Url::make_relative` makes a string *without* a leading slash, so we'll have to copy the whole URL-path into a new string with such a slash.
+		if let Some(robots) = self.robots {
+		if let Some(exclusion) = robots.check(format!("/{url_rel}").as_str()) {
+			self.robots_excluded_count += 1;
+
+			if self.s.cfg.warn_robots_txt_exclusions {
+				eprintln!("Warning: `{url_rel}` (at file path `{}`) is included by the rules, but {exclusion}", path.display());
+			}
+			else {
+				explain_exclude(&format_args!("It is included by the rules, but {exclusion}"));
+			}
+
+			return Ok(());
+		}}
+
+		// Check if this file is excluded by an `X-Robots-Tag` header in the headers file.
+		if let Some(headers_file) = self.headers_file {
+		if let Some(exclusion) = headers_file.check(&web_url, &self.s.cfg.meta_robots_agents, self.now) {
+			explain_exclude(&exclusion);
+			return Ok(());
+		}}
+
+		let is_xhtml: bool =
+			path.extension()
+			.is_some_and(|ext| ext.eq_ignore_ascii_case("xhtml") || ext.eq_ignore_ascii_case("xht"));
+
+		// The HTML sitemap uses the titles and descriptions of HTML pages, which are recognized by their file name extension.
+		let want_html_sitemap_info: bool =
+			self.s.cfg.html_sitemap_path.is_some()
+			&& (is_xhtml || path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm")));
+
+		let mut title: Option<String> = None;
+		let mut description: Option<String> = None;
+
+		// If this file is HTML, check it for `<meta name=robots>` and `<meta http-equiv=refresh>`.
+		if let Some(fd) = &mut content {
+		if applied_rules.check_html_meta_robots || applied_rules.html_redirect != HtmlRedirect::Include || want_html_sitemap_info {
+			// XHTML files are parsed as XML, unless a rule says otherwise.
+			let html_syntax: HtmlSyntax = applied_rules.html_syntax.unwrap_or(
+				if is_xhtml { HtmlSyntax::Xml } else { HtmlSyntax::Html }
+			);
+
+			// Web browsers obey `<meta http-equiv=refresh>` anywhere in the document, so look for it in the `<body>` too, if it matters.
+			let read_body: bool = applied_rules.html_redirect != HtmlRedirect::Include;
+
+			let html_meta = match html_syntax {
+				HtmlSyntax::Html => self::check_html_meta::HtmlMeta::read(fd, self.s.cfg, read_body),
+				HtmlSyntax::Xml => self::check_html_meta::HtmlMeta::read_xhtml(fd, self.s.cfg, read_body),
+			}
+			.with_context(|| format!("couldn't read HTML file `{}`", path.display()))?;
+
+			if applied_rules.check_html_meta_robots {
+			if let Some(robots) = html_meta.no_index(self.now) {
+				explain_exclude(&format_args!("It's an HTML page with `<meta name={} content=\"{}\">`.", robots.name, robots.content));
+				return Ok(());
 			}}
 
-			// If this file is HTML, check it for `<meta name=robots>`.
 			if applied_rules.check_html_meta_robots {
-				let html_meta =
-					self::check_html_meta::HtmlMeta::read(&mut fd)
-					.with_context(|| format!("couldn't read HTML file `{}`", dent_path.display()))?;
+			if let Some(error_page) = self.error_pages.check_html(&html_meta) {
+				explain_exclude(&error_page);
+				return Ok(());
+			}}
 
-				if html_meta.no_index {
-					explain_exclude(&"It's an HTML page with `<meta name=robots content=noindex>`.");
-					continue;
+			// The title and description of a redirecting page are not those of its target, so they're dropped if the page is replaced by its target.
+			let mut is_replaced_by_redirect_target = false;
+
+			if let Some(redirect) = &html_meta.redirect {
+				let redirect_tag = format_args!("<meta http-equiv=refresh content=\"{}\">", redirect.content);
+
+				match applied_rules.html_redirect {
+					HtmlRedirect::Include => {}
+
+					HtmlRedirect::Exclude => {
+						explain_exclude(&format_args!("It's an HTML page that redirects elsewhere, with `{redirect_tag}`."));
+						return Ok(());
+					}
+
+					HtmlRedirect::Replace => {
+						let target: Option<Url> =
+							web_url.join(&redirect.url).ok()
+							.map(|mut target| {
+								target.set_fragment(None);
+								target
+							})
+							.filter(|target| target.as_str().starts_with(self.s.cfg.root_url.as_str()));
+
+						let Some(target) = target else {
+							explain_exclude(&format_args!("It's an HTML page that redirects to a URL outside of the `root_url`, with `{redirect_tag}`."));
+							return Ok(());
+						};
+
+						// The target takes this page's place in the sitemap, so it has to pass the same checks that this page did.
+						let target_rel: &str = &target.as_str()[self.s.cfg.root_url.as_str().len()..];
+
+						if let Some(error_page) = self.error_pages.check_file_name(target.path_segments().and_then(|mut segments| segments.next_back()).unwrap_or_default()) {
+							explain_exclude(&format_args!("It's an HTML page that redirects to `{target}`, with `{redirect_tag}`. {error_page}"));
+							return Ok(());
+						}
+
+						if let Some(robots) = self.robots {
+						if let Some(exclusion) = robots.check(format!("/{target_rel}").as_str()) {
+							self.robots_excluded_count += 1;
+
+							if self.s.cfg.warn_robots_txt_exclusions {
+								eprintln!("Warning: `{url_rel}` (at file path `{}`) is included by the rules and redirects to `{target}`, with `{redirect_tag}`, but {exclusion}", path.display());
+							}
+							else {
+								explain_exclude(&format_args!("It's an HTML page that redirects to `{target}`, with `{redirect_tag}`, but {exclusion}"));
+							}
+
+							return Ok(());
+						}}
+
+						if let Some(headers_file) = self.headers_file {
+						if let Some(exclusion) = headers_file.check(&target, &self.s.cfg.meta_robots_agents, self.now) {
+							explain_exclude(&format_args!("It's an HTML page that redirects to `{target}`, with `{redirect_tag}`. {exclusion}"));
+							return Ok(());
+						}}
+
+						if self.s.cmd.verbose {
+							eprintln!("Replacing `{web_url}` with `{target}`, because it's an HTML page that redirects there with `{redirect_tag}`.");
+						}
+
+						web_url = target;
+						is_replaced_by_redirect_target = true;
+					}
 				}
 			}
 
-			// We can close the file now.
-			drop(fd);
+			if applied_rules.check_html_meta_robots && self.s.cmd.verbose {
+			if let Some(robots) = html_meta.ignored_robots.iter().find(|robots| robots.directives.excludes(self.now)) {
+				eprintln!(
+					"Warning: `{url_rel}` (at file path `{}`) has `<meta name={} content=\"{}\">`, but it was ignored because of where it is in the document; see the `meta_robots_position` setting.",
+					path.display(),
+					robots.name,
+					robots.content,
+				);
+			}}
 
-			// Start constructing a `UrlEntry`.
-			let mut entry = sitemap::structs::UrlEntry::builder();
-			entry = entry.loc(web_url);
+			if self.s.cmd.verbose {
+			if let Some(canonical) = &html_meta.canonical {
+			if web_url.join(canonical).ok().as_ref() != Some(&web_url) {
+				eprintln!(
+					"Warning: `{url_rel}` (at file path `{}`) has `<link rel=canonical href=\"{canonical}\">`, which isn't its URL in the sitemap, `{web_url}`.",
+					path.display(),
+				);
+			}}}
+
+			if !is_replaced_by_redirect_target {
+				title = html_meta.title;
+				description = html_meta.description;
+			}
+		}}
 
-			// Convert the last-modified time.
-			if let Some(t) = md.modified().ok().map(|t| {
-				use chrono::*;
+		// If this file is a PDF, check its metadata for a noindex marker.
+		if let Some(fd) = &mut content {
+		if applied_rules.check_pdf_noindex {
+			// The file may have already been partly read, if it was checked for HTML `<meta>` elements too.
+			fd.rewind()
+			.with_context(|| format!("couldn't read file `{}`", path.display()))?;
 
-				// Convert the time stamp to `chrono::DateTime` in UTC.
-				let t = DateTime::<Utc>::from(t);
+			let content_type: &str = match content_type {
+				Some(content_type) => content_type,
+				None => {
+					self::sniff_content_type::sniff_content_type(fd)
+					.with_context(|| format!("couldn't read file `{}`", path.display()))?
+				}
+			};
 
-				// Convert the time stamp to a Unix timestamp.
-				let t = t.timestamp();
+			if content_type == "application/pdf" {
+				let mut buffer = Vec::new();
 
-				// Convert the time stamp to a `NaiveDateTime`. This is the same as before, but rounded to a whole second.
-				let t = NaiveDateTime::from_timestamp(t, 0);
+				fd.read_to_end(&mut buffer)
+				.with_context(|| format!("couldn't read file `{}`", path.display()))?;
 
-				// Convert the time stamp back to `DateTime<Utc>`.
-				let t = DateTime::<Utc>::from_utc(t, Utc);
+				match self::check_pdf_meta::PdfMeta::parse(&buffer, self.s.cfg) {
+					Ok(pdf_meta) => {
+						if let Some(no_index) = &pdf_meta.no_index {
+							explain_exclude(no_index);
+							return Ok(());
+						}
+					}
 
-				// Finally, convert it to the representation `sitemap` wants.
-				DateTime::<FixedOffset>::from(t)
-			}) {
-				entry = entry.lastmod(t);
+					Err(error) => {
+						eprintln!("Warning: `{url_rel}` (at file path `{}`) looks like a PDF document, but it couldn't be read as one, so its metadata wasn't checked: {error}", path.display());
+					}
+				}
 			}
+		}}
 
-			// Insert it.
-			self.urls.push(
+		// Start constructing a `UrlEntry`.
+		let mut entry = sitemap::structs::UrlEntry::builder();
+		entry = entry.loc(web_url);
+
+		// Use the last-modified time, unless the front matter has one.
+		// Convert it to the representation `sitemap` wants.
+		if let Some(t) = front_matter.lastmod.or(modified) {
+			entry = entry.lastmod(chrono::DateTime::<chrono::FixedOffset>::from(t));
+		}
+
+		// Insert it.
+		self.urls.push(ScannedUrl {
+			entry:
 				entry.build()
-				.context("couldn't generate sitemap entry")?
+				.context("couldn't generate sitemap entry")?,
+			title,
+			description,
+		});
+
+		Ok(())
+	}
+
+	/// Fails if `robots.txt` excluded too many of the files that the rules say to include, according to [`Config::max_robots_txt_exclusion_percent`].
+	fn check_robots_excluded_count(&self) -> anyhow::Result<()> {
+		if self.robots_excluded_count == 0 {
+			return Ok(());
+		}
+
+		let percent: f64 = self.robots_excluded_count as f64 / self.rules_included_count as f64 * 100.;
+
+		if self.s.cmd.verbose {
+			eprintln!(
+				"`robots.txt` excluded {} of the {} files that the rules say to include ({percent:.1}%).",
+				self.robots_excluded_count,
+				self.rules_included_count,
 			);
+		}
 
-			// Make sure not to exceed 50k URLs.
+		if let Some(max_percent) = self.s.cfg.max_robots_txt_exclusion_percent {
 			anyhow::ensure!(
-				self.urls.len() <= MAX_SITEMAP_URLS,
-				"more than {MAX_SITEMAP_URLS} files are to be included in the sitemap, which is not allowed by the sitemaps protocol; please divide the files into multiple sitemaps and join them together in a sitemap index",
+				percent <= max_percent,
+				"`robots.txt` excluded {} of the {} files that the rules say to include ({percent:.1}%), which is more than the configured `max_robots_txt_exclusion_percent` of {max_percent}%; please check `robots.txt` for mistakes (such as `Disallow: /`)",
+				self.robots_excluded_count,
+				self.rules_included_count,
 			);
 		}
 
 		Ok(())
 	}
 }
+
+/// Converts a file's last-modified time to `chrono::DateTime`, rounded to a whole second.
+pub fn file_time(t: SystemTime) -> chrono::DateTime<chrono::Utc> {
+	use chrono::*;
+
+	// Convert the time stamp to `chrono::DateTime` in UTC.
+	let t = DateTime::<Utc>::from(t);
+
+	// Convert it to a Unix timestamp and back, rounding it to a whole second. That always works for a time that came from a `DateTime` in the first place, but if it somehow doesn't, use the unrounded time.
+	Utc.timestamp_opt(t.timestamp(), 0).single().unwrap_or(t)
+}
@@ -0,0 +1,303 @@
+use anyhow::Context as _;
+use chrono::{DateTime, NaiveDate, TimeZone as _, Utc};
+use std::{
+	fs::File,
+	io::{self, BufReader, Read, Seek, SeekFrom},
+	path::{Component, Path, PathBuf},
+};
+
+/// A tar, gzip-compressed tar, or zip archive containing the files of a website, to be scanned instead of the `root_dir`.
+pub struct Archive<'p> {
+	path: &'p Path,
+	format: ArchiveFormat,
+
+	/// The folder in the archive that holds the website, if it isn't the top level of the archive. See [`crate::config::Config::archive_strip_prefix`].
+	strip_prefix: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy)]
+enum ArchiveFormat {
+	Tar,
+	TarGz,
+	Zip,
+}
+
+/// A regular file in an [`Archive`].
+pub struct ArchiveFile<'a> {
+	/// The path of the file inside the archive, without any leading `./` or [`Archive::strip_prefix`].
+	pub path: PathBuf,
+
+	/// The file's last modification time, rounded to a whole second.
+	pub modified: Option<DateTime<Utc>>,
+
+	/// The file's content, read from the archive as needed.
+	pub content: Rewindable<&'a mut dyn Read>,
+}
+
+/// Another kind of entry in an [`Archive`], like a folder or a symbolic link.
+pub struct ArchiveOther {
+	pub path: PathBuf,
+	pub is_dir: bool,
+}
+
+impl<'p> Archive<'p> {
+	/// Figures out what kind of archive the file at `path` is, by looking at its first few bytes.
+	///
+	/// If `strip_prefix` is given, only the entries in that folder of the archive are considered, and their paths are taken to be relative to it.
+	pub fn open(path: &'p Path, strip_prefix: Option<&'p Path>) -> anyhow::Result<Self> {
+		let mut header: Vec<u8> = Vec::new();
+
+		File::open(path)
+		.and_then(|fd| fd.take(262).read_to_end(&mut header))
+		.with_context(|| format!("couldn't read archive `{}`", path.display()))?;
+
+		let format =
+			if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+				ArchiveFormat::Zip
+			}
+			else if header.starts_with(b"\x1F\x8B") {
+				ArchiveFormat::TarGz
+			}
+			else if header.get(257..262) == Some(b"ustar") {
+				ArchiveFormat::Tar
+			}
+			else {
+				anyhow::bail!("`{}` is not a tar, tar.gz, or zip archive", path.display());
+			};
+
+		// Ignore any `./` in the prefix, just like in the entries' paths.
+		let strip_prefix: Option<PathBuf> = strip_prefix.map(|prefix| {
+			prefix.components()
+			.filter(|component| !matches!(component, Component::CurDir))
+			.collect()
+		});
+
+		Ok(Self { path, format, strip_prefix })
+	}
+
+	/// The path to the archive file.
+	pub fn path(&self) -> &'p Path {
+		self.path
+	}
+
+	/// Calls `f` for each regular file in the archive, and `other` for each other entry, in the order they appear in the archive. Stops early if `f` returns `Ok(false)`.
+	pub fn for_each_entry(
+		&self,
+		mut f: impl FnMut(ArchiveFile<'_>) -> anyhow::Result<bool>,
+		mut other: impl FnMut(ArchiveOther),
+	) -> anyhow::Result<()> {
+		let fd =
+			File::open(self.path)
+			.with_context(|| format!("couldn't open archive `{}`", self.path.display()))?;
+
+		let fd = BufReader::new(fd);
+
+		match self.format {
+			ArchiveFormat::Tar => self.for_each_tar_entry(fd, &mut f, &mut other),
+			ArchiveFormat::TarGz => self.for_each_tar_entry(flate2::read::GzDecoder::new(fd), &mut f, &mut other),
+			ArchiveFormat::Zip => self.for_each_zip_entry(fd, &mut f, &mut other),
+		}
+	}
+
+	/// Reads some regular files from the archive, in a single pass over it. Each element of the result is the content of the file at the corresponding path, or `None` if the path is `None` or there's no such file.
+	///
+	/// The pass stops as soon as all of the files have been found. Other files' contents aren't read.
+	pub fn read_files<const N: usize>(&self, paths: [Option<&Path>; N]) -> anyhow::Result<[Option<Vec<u8>>; N]> {
+		let mut found: [Option<Vec<u8>>; N] = std::array::from_fn(|_| None);
+		let mut remaining: usize = paths.iter().flatten().count();
+
+		if remaining == 0 {
+			return Ok(found);
+		}
+
+		self.for_each_entry(
+			|mut file| {
+				if let Some(index) = paths.iter().position(|path| *path == Some(file.path.as_path())) {
+				if found[index].is_none() {
+					let mut content: Vec<u8> = Vec::new();
+
+					file.content.read_to_end(&mut content)
+					.with_context(|| format!("couldn't read `{}` in archive `{}`", file.path.display(), self.path.display()))?;
+
+					found[index] = Some(content);
+					remaining -= 1;
+				}}
+
+				Ok(remaining > 0)
+			},
+			|_| {},
+		)?;
+
+		Ok(found)
+	}
+
+	fn for_each_tar_entry(
+		&self,
+		input: impl Read,
+		f: &mut dyn FnMut(ArchiveFile<'_>) -> anyhow::Result<bool>,
+		other: &mut dyn FnMut(ArchiveOther),
+	) -> anyhow::Result<()> {
+		let mut archive = tar::Archive::new(input);
+
+		let entries =
+			archive.entries()
+			.with_context(|| format!("couldn't read archive `{}`", self.path.display()))?;
+
+		for entry in entries {
+			let mut entry = entry.with_context(|| format!("couldn't read archive `{}`", self.path.display()))?;
+
+			let Some(path) = self.entry_path(
+				&entry.path()
+				.with_context(|| format!("couldn't read the path of an entry in archive `{}`", self.path.display()))?
+			)? else {
+				continue;
+			};
+
+			if !entry.header().entry_type().is_file() {
+				other(ArchiveOther { path, is_dir: entry.header().entry_type().is_dir() });
+				continue;
+			}
+
+			let modified: Option<DateTime<Utc>> =
+				entry.header().mtime().ok()
+				.and_then(|mtime| i64::try_from(mtime).ok())
+				.and_then(|mtime| Utc.timestamp_opt(mtime, 0).single());
+
+			let content = Rewindable::new(&mut entry as &mut dyn Read);
+
+			if !f(ArchiveFile { path, modified, content })? {
+				break;
+			}
+		}
+
+		Ok(())
+	}
+
+	fn for_each_zip_entry(
+		&self,
+		input: impl Read + io::Seek,
+		f: &mut dyn FnMut(ArchiveFile<'_>) -> anyhow::Result<bool>,
+		other: &mut dyn FnMut(ArchiveOther),
+	) -> anyhow::Result<()> {
+		let mut archive =
+			zip::ZipArchive::new(input)
+			.with_context(|| format!("couldn't read archive `{}`", self.path.display()))?;
+
+		for index in 0..archive.len() {
+			let mut entry =
+				archive.by_index(index)
+				.with_context(|| format!("couldn't read archive `{}`", self.path.display()))?;
+
+			let Some(path) = self.entry_path(Path::new(entry.name()))? else {
+				continue;
+			};
+
+			if !entry.is_file() {
+				other(ArchiveOther { path, is_dir: entry.is_dir() });
+				continue;
+			}
+
+			// Zip archives store times as local time, without a time zone, so take them to be in UTC, just like dates in `unavailable_after` robots directives.
+			let modified: Option<DateTime<Utc>> = {
+				let t = entry.last_modified();
+
+				NaiveDate::from_ymd_opt(t.year().into(), t.month().into(), t.day().into())
+				.and_then(|date| date.and_hms_opt(t.hour().into(), t.minute().into(), t.second().into()))
+				.map(|t| Utc.from_utc_datetime(&t))
+			};
+
+			let content = Rewindable::new(&mut entry as &mut dyn Read);
+
+			if !f(ArchiveFile { path, modified, content })? {
+				break;
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Normalizes the path of an entry, removing any leading `./` and the [`Archive::strip_prefix`]. The result is `None` if the entry isn't inside the `strip_prefix`. Paths that lead outside of the archive, like `../index.html` or `/index.html`, are an error.
+	fn entry_path(&self, path: &Path) -> anyhow::Result<Option<PathBuf>> {
+		let mut normalized = PathBuf::new();
+
+		for component in path.components() {
+			match component {
+				Component::Normal(name) => normalized.push(name),
+				Component::CurDir => {}
+				Component::ParentDir | Component::RootDir | Component::Prefix(_) => anyhow::bail!(
+					"archive `{}` contains an entry at `{}`, which is outside of the archive",
+					self.path.display(),
+					path.display(),
+				),
+			}
+		}
+
+		match &self.strip_prefix {
+			Some(prefix) => Ok(normalized.strip_prefix(prefix).ok().map(Path::to_path_buf)),
+			None => Ok(Some(normalized)),
+		}
+	}
+}
+
+/// Reads from an archive entry, keeping what was read so that it can be read again after seeking back, since archive entries can only be read once, from start to end.
+///
+/// Only as much of the entry is read as is asked for, so a file that's only checked for `<meta>` elements in its `<head>` isn't decompressed in full, for example.
+pub struct Rewindable<R> {
+	inner: R,
+	buffer: Vec<u8>,
+	position: usize,
+}
+
+impl<R: Read> Rewindable<R> {
+	pub fn new(inner: R) -> Self {
+		Self {
+			inner,
+			buffer: Vec::new(),
+			position: 0,
+		}
+	}
+}
+
+impl<R: Read> Read for Rewindable<R> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		if let Some(buffered) = self.buffer.get(self.position..).filter(|buffered| !buffered.is_empty()) {
+			let len = buffered.len().min(buf.len());
+			buf[..len].copy_from_slice(&buffered[..len]);
+			self.position += len;
+			return Ok(len);
+		}
+
+		// Past the end of the entry, after seeking there.
+		if self.position > self.buffer.len() {
+			return Ok(0);
+		}
+
+		let len = self.inner.read(buf)?;
+		self.buffer.extend_from_slice(&buf[..len]);
+		self.position += len;
+		Ok(len)
+	}
+}
+
+impl<R: Read> Seek for Rewindable<R> {
+	fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+		let target: u64 = match pos {
+			SeekFrom::Start(offset) => offset,
+
+			SeekFrom::Current(offset) => {
+				(self.position as u64).checked_add_signed(offset)
+				.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid seek to a negative or overflowing position"))?
+			}
+
+			SeekFrom::End(_) => return Err(io::Error::new(io::ErrorKind::Unsupported, "can't seek relative to the end of an archive entry")),
+		};
+
+		// Read up to the target position, if it hasn't been read yet.
+		if let Some(unread) = target.checked_sub(self.buffer.len() as u64).filter(|unread| *unread > 0) {
+			(&mut self.inner).take(unread).read_to_end(&mut self.buffer)?;
+		}
+
+		self.position = usize::try_from(target).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "seek position is too large"))?;
+		Ok(target)
+	}
+}
@@ -1,4 +1,6 @@
-use html5ever::tendril::TendrilSink as _;
+use chrono::{DateTime, Utc};
+use crate::config::{Config, MetaRobotsPosition};
+use html5ever::tendril::{StrTendril, TendrilSink as _};
 use markup5ever::{
 	expanded_name,
 	local_name,
@@ -11,6 +13,10 @@ use std::{
 	io::{self, Read},
 	rc::Rc,
 };
+use super::{
+	robots_directives::RobotsDirectives,
+	sniff_html_encoding::{PRESCAN_LEN, sniff_html_encoding},
+};
 
 #[cfg(debug_assertions)]
 use std::{
@@ -18,22 +24,119 @@ use std::{
 	rc::Weak as RcWeak,
 };
 
+/// How many bytes of an HTML file to decode and parse at a time.
+const CHUNK_LEN: u64 = 8192;
+
 pub struct HtmlMeta {
-	pub no_index: bool,
+	/// The robots meta tags in the document's `<head>` that are for one of the [`Config::meta_robots_agents`], in document order.
+	pub robots: Vec<MetaRobots>,
+
+	/// Robots meta tags for those same bots that were ignored because of where they are in the document. See [`Config::meta_robots_position`].
+	pub ignored_robots: Vec<MetaRobots>,
+
+	/// The text of the document's `<title>`, with whitespace collapsed.
+	pub title: Option<String>,
+
+	/// The `content` of the document's `<meta name=description>`, with whitespace collapsed.
+	pub description: Option<String>,
+
+	/// The `href` of the document's `<link rel=canonical>`, as written. With `--verbose`, a warning is shown if it isn't the page's URL in the sitemap.
+	pub canonical: Option<String>,
+
+	/// The target of the document's `<meta http-equiv=refresh>`, if it has one with a URL.
+	pub redirect: Option<MetaRefresh>,
+
+	/// The document's `<meta name=prerender-status-code>` or similar element, if it has one. See [`STATUS_CODE_META_NAMES`].
+	pub status_code: Option<MetaStatusCode>,
+
 	#[cfg(debug_assertions)]
 	all_nodes: Vec<RcWeak<Node>>,
 }
 
+/// A `<meta>` element for a bot, like `<meta name=robots content=noindex>` or `<meta name=googlebot content=none>`.
+pub struct MetaRobots {
+	/// The `name` attribute, in lowercase.
+	pub name: String,
+
+	/// The `content` attribute, as written.
+	pub content: String,
+
+	/// The `content` attribute, parsed.
+	pub directives: RobotsDirectives,
+}
+
+/// Names of `<meta>` elements that some static site generators and prerendering tools use to say what HTTP status code a page should be served with, like `<meta name=prerender-status-code content=404>`.
+const STATUS_CODE_META_NAMES: &[&str] = &[
+	"prerender-status-code",
+	"render:status_code",
+];
+
+/// A `<meta>` element giving the HTTP status code of the page. See [`STATUS_CODE_META_NAMES`].
+pub struct MetaStatusCode {
+	/// The `name` attribute, in lowercase.
+	pub name: String,
+
+	/// The `content` attribute, as written.
+	pub content: String,
+
+	/// The status code, if the `content` is a valid one.
+	pub status_code: Option<u16>,
+}
+
+/// A `<meta http-equiv=refresh>` element that redirects to another URL, like `<meta http-equiv=refresh content="0; url=/new/">`.
+pub struct MetaRefresh {
+	/// The `content` attribute, as written.
+	pub content: String,
+
+	/// The URL to redirect to, as written. It may be relative.
+	pub url: String,
+}
+
 impl HtmlMeta {
-	pub fn read(input: &mut impl Read) -> io::Result<Self> {
+	/// Parses an HTML document and collects the `<meta>` elements for the [`Config::meta_robots_agents`]. Bot names are compared case-insensitively.
+	///
+	/// Reading stops once `<body>` starts, unless [`Config::meta_robots_position`] is [`MetaRobotsPosition::Anywhere`] or `read_body` is true. `read_body` is for finding `<meta http-equiv=refresh>` elements in the `<body>`, which web browsers obey too.
+	pub fn read(input: &mut impl Read, cfg: &Config, read_body: bool) -> io::Result<Self> {
 		let mut opts = html5ever::ParseOpts::default();
 		opts.tree_builder.scripting_enabled = false;
 
+		let mut parser = html5ever::parse_document(HtmlSink::new(cfg), opts);
+
+		// Don't read more than the configured maximum.
+		let mut input = input.take(cfg.max_html_read_bytes.unwrap_or(u64::MAX));
+
+		// Figure out the encoding from the first few bytes, then decode and parse the file a chunk at a time.
+		let mut chunk: Vec<u8> = Vec::new();
+		input.by_ref().take(PRESCAN_LEN).read_to_end(&mut chunk)?;
+
+		let encoding = sniff_html_encoding(chunk.as_slice(), cfg.html_fallback_encoding);
+		let mut decoder = encoding.new_decoder();
+		let mut decoded = String::new();
+
+		loop {
+			let last: bool = chunk.is_empty();
+
+			decoded.clear();
+			decoded.reserve(
+				decoder.max_utf8_buffer_length(chunk.len())
+				.expect("decoded HTML chunk size overflows `usize`")
+			);
+
+			let (result, _, _) = decoder.decode_to_string(chunk.as_slice(), &mut decoded, last);
+			debug_assert_eq!(result, encoding_rs::CoderResult::InputEmpty, "`decoded` wasn't big enough for the whole chunk");
+			parser.process(StrTendril::from_slice(decoded.as_str()));
+
+			// Stop at the end of the file, or once `<body>` starts if only the `<head>` matters.
+			if last || parser.tokenizer.sink.sink.body_started && !read_body && !matches!(cfg.meta_robots_position, MetaRobotsPosition::Anywhere) {
+				break;
+			}
+
+			chunk.clear();
+			input.by_ref().take(CHUNK_LEN).read_to_end(&mut chunk)?;
+		}
+
 		#[cfg_attr(not(debug_assertions), allow(unused_mut))]
-		let mut result =
-			html5ever::parse_document(HtmlSink::new(), opts)
-			.from_utf8()
-			.read_from(input)?;
+		let mut result = parser.finish();
 
 		#[cfg(debug_assertions)] {
 			// In debug builds, make sure we free all our memory.
@@ -48,6 +151,188 @@ impl HtmlMeta {
 
 		Ok(result)
 	}
+
+	/// Parses a well-formed XHTML document with an XML parser, and collects the same information as [`HtmlMeta::read`].
+	///
+	/// Only elements in the XHTML namespace are recognized. HTML's named character references (like `&nbsp;`) are understood, as they are by web browsers for documents with an XHTML `<!DOCTYPE>`. Reading stops at the first XML syntax error, like the end of a file cut short by [`Config::max_html_read_bytes`], and whatever was found before it is kept. I/O errors are still returned.
+	pub fn read_xhtml(input: &mut impl Read, cfg: &Config, read_body: bool) -> io::Result<Self> {
+		use xml::reader::{ErrorKind, XmlEvent};
+
+		const XHTML_NS: &str = "http://www.w3.org/1999/xhtml";
+
+		let input = input.take(cfg.max_html_read_bytes.unwrap_or(u64::MAX));
+
+		// XML itself only defines `&lt;`, `&gt;`, `&amp;`, `&apos;`, and `&quot;`. `NAMED_ENTITIES` has each of HTML's other names both with and without the trailing `;`; only the ones with it are valid in XML.
+		let parser_config =
+			markup5ever::data::NAMED_ENTITIES.entries()
+			.filter_map(|(name, codepoints)| Some((name.strip_suffix(';')?, codepoints)))
+			.fold(
+				xml::ParserConfig::new().replace_unknown_entity_references(true),
+				|parser_config, (name, &(first, second))| {
+					let value: String =
+						[first, second].into_iter()
+						.filter(|codepoint| *codepoint != 0)
+						.filter_map(char::from_u32)
+						.collect();
+
+					parser_config.add_entity(name, value)
+				},
+			);
+
+		let reader = xml::EventReader::new_with_config(input, parser_config);
+
+		let mut result = Self::new();
+
+		// The local names of the currently open elements, outermost first. Elements that aren't in the XHTML namespace are represented by an empty string.
+		let mut open_elements: Vec<String> = Vec::new();
+
+		// While inside the first `<title>`, the number of elements open outside of it, and the text found in it so far.
+		let mut title: Option<(usize, String)> = None;
+
+		for event in reader {
+			let event = match event {
+				Ok(event) => event,
+				Err(error) => match error.kind() {
+					ErrorKind::Io(io_error) => return Err(io::Error::new(io_error.kind(), error.to_string())),
+					_ => break,
+				},
+			};
+
+			match event {
+				XmlEvent::StartElement { name, attributes, .. } => {
+					let local_name: String =
+						if name.namespace_ref() == Some(XHTML_NS) {
+							name.local_name
+						}
+						else {
+							String::new()
+						};
+
+					let get_attr = |attr_name: &str| -> Option<String> {
+						attributes.iter()
+						.find(|attr| attr.name.namespace.is_none() && attr.name.local_name == attr_name)
+						.map(|attr| attr.value.clone())
+					};
+
+					let is_in_document: bool = !open_elements.iter().any(|name| name == "template");
+					let is_in_head: bool = is_in_document && open_elements.first().is_some_and(|name| name == "html") && open_elements.iter().any(|name| name == "head");
+
+					match local_name.as_str() {
+						"meta" => {
+							if let (Some(meta_name), Some(content)) = (get_attr("name"), get_attr("content")) {
+								let position_ok: bool = match cfg.meta_robots_position {
+									MetaRobotsPosition::Strict => open_elements == ["html", "head"],
+									MetaRobotsPosition::HeadAnywhere => is_in_head,
+									MetaRobotsPosition::Anywhere => is_in_document,
+								};
+
+								result.add_meta(cfg, meta_name.to_ascii_lowercase(), content, position_ok);
+							}
+							else if let (Some(http_equiv), Some(content)) = (get_attr("http-equiv"), get_attr("content")) {
+							if http_equiv.eq_ignore_ascii_case("refresh") && result.redirect.is_none() && is_in_document {
+								result.redirect = MetaRefresh::parse(content);
+							}}
+						}
+
+						"link" if result.canonical.is_none() && is_in_head && get_attr("rel").is_some_and(|rel| is_canonical(&rel)) => {
+							result.canonical = get_attr("href");
+						}
+
+						"title" if result.title.is_none() && title.is_none() && is_in_document => {
+							title = Some((open_elements.len(), String::new()));
+						}
+
+						// Stop once `<body>` starts if only the `<head>` matters.
+						"body" | "frameset" if !read_body && !matches!(cfg.meta_robots_position, MetaRobotsPosition::Anywhere) => break,
+
+						_ => {}
+					}
+
+					open_elements.push(local_name);
+				}
+
+				XmlEvent::EndElement { .. } => {
+					open_elements.pop();
+
+					if let Some((depth, text)) = &title {
+					if open_elements.len() == *depth {
+						result.title = Some(collapse_whitespace(text));
+						title = None;
+					}}
+				}
+
+				XmlEvent::Characters(text) | XmlEvent::CData(text) | XmlEvent::Whitespace(text) => {
+					if let Some((_, title_text)) = &mut title {
+						title_text.push_str(&text);
+					}
+				}
+
+				_ => {}
+			}
+		}
+
+		Ok(result)
+	}
+
+	fn new() -> Self {
+		Self {
+			robots: Vec::new(),
+			ignored_robots: Vec::new(),
+			title: None,
+			description: None,
+			canonical: None,
+			redirect: None,
+			status_code: None,
+			#[cfg(debug_assertions)]
+			all_nodes: Vec::new(),
+		}
+	}
+
+	/// Records a `<meta name=… content=…>` element, if it's a description, gives a status code, or is for one of the [`Config::meta_robots_agents`]. `name` must be in lowercase.
+	fn add_meta(&mut self, cfg: &Config, name: String, content: String, position_ok: bool) {
+		if name == "description" {
+			if position_ok && self.description.is_none() {
+				self.description = Some(collapse_whitespace(&content));
+			}
+
+			return;
+		}
+
+		if STATUS_CODE_META_NAMES.contains(&name.as_str()) {
+			if position_ok && self.status_code.is_none() {
+				self.status_code = Some(MetaStatusCode {
+					status_code: content.trim().parse().ok(),
+					name,
+					content,
+				});
+			}
+
+			return;
+		}
+
+		if !cfg.meta_robots_agents.iter().any(|agent| agent.eq_ignore_ascii_case(&name)) {
+			return;
+		}
+
+		let robots = MetaRobots {
+			directives: RobotsDirectives::parse(&content),
+			name,
+			content,
+		};
+
+		if position_ok {
+			self.robots.push(robots);
+		}
+		else {
+			self.ignored_robots.push(robots);
+		}
+	}
+
+	/// Finds a robots meta tag that says not to index the page at the given time, if there is one.
+	pub fn no_index(&self, now: DateTime<Utc>) -> Option<&MetaRobots> {
+		self.robots.iter()
+		.find(|robots| robots.directives.excludes(now))
+	}
 }
 
 #[derive(Debug)]
@@ -72,20 +357,29 @@ impl Node {
 
 	fn set_attr(&self, attr: &html5ever::Attribute) {
 		if let Some(element) = self.as_element() {
-		if element.name.expanded() == expanded_name!(html "meta") {
 			let name = attr.name.expanded();
 			let value = attr.value.as_ref();
 
-			if name == expanded_name!("", "name") {
-				element.is_meta_name_robots.set(value == "robots");
+			if element.name.expanded() == expanded_name!(html "meta") {
+				if name == expanded_name!("", "name") {
+					element.name_attr.replace(Some(value.to_ascii_lowercase()));
+				}
+				else if name == expanded_name!("", "content") {
+					element.content_attr.replace(Some(value.to_owned()));
+				}
+				else if name == expanded_name!("", "http-equiv") {
+					element.http_equiv_attr.replace(Some(value.to_ascii_lowercase()));
+				}
 			}
-			else if name == expanded_name!("", "content") {
-				element.is_meta_content_noindex.set(
-					value.split(',')
-					.any(|part| part.trim().eq_ignore_ascii_case("noindex"))
-				);
+			else if element.name.expanded() == expanded_name!(html "link") {
+				if name == expanded_name!("", "rel") {
+					element.is_link_canonical.set(is_canonical(value));
+				}
+				else if name == expanded_name!("", "href") {
+					element.href_attr.replace(Some(value.to_owned()));
+				}
 			}
-		}}
+		}
 	}
 
 	fn as_element(&self) -> Option<&Element> {
@@ -108,20 +402,42 @@ enum NodeKind {
 #[derive(Debug)]
 struct Element {
 	name: html5ever::QualName,
-	is_meta_name_robots: Cell<bool>,
-	is_meta_content_noindex: Cell<bool>,
+
+	/// For `<meta>`, the `name` attribute, in lowercase.
+	name_attr: RefCell<Option<String>>,
+
+	/// For `<meta>`, the `content` attribute.
+	content_attr: RefCell<Option<String>>,
+
+	/// For `<meta>`, the `http-equiv` attribute, in lowercase.
+	http_equiv_attr: RefCell<Option<String>>,
+
+	/// For `<link>`, whether the `rel` attribute contains `canonical`.
+	is_link_canonical: Cell<bool>,
+
+	/// For `<link>`, the `href` attribute.
+	href_attr: RefCell<Option<String>>,
+
+	/// For `<title>`, the text inside it.
+	text: RefCell<String>,
 }
 
-#[derive(Debug)]
-struct HtmlSink {
+struct HtmlSink<'a> {
+	cfg: &'a Config,
 	document: Rc<Node>,
-	meta_elements: Vec<Rc<Node>>,
+
+	/// The `<meta>`, `<link>`, and `<title>` elements created so far.
+	elements: Vec<Rc<Node>>,
+
+	/// Whether a `<body>` (or `<frameset>`) element has been created, meaning that the `<head>` is finished.
+	body_started: bool,
+
 	#[cfg(debug_assertions)]
 	all_nodes: Vec<RcWeak<Node>>,
 }
 
-impl HtmlSink {
-	fn new() -> Self {
+impl<'a> HtmlSink<'a> {
+	fn new(cfg: &'a Config) -> Self {
 		let document = Node::new(NodeKind::Document);
 
 		Self {
@@ -129,8 +445,10 @@ impl HtmlSink {
 			all_nodes: Vec::from([
 				Rc::downgrade(&document),
 			]),
+			cfg,
 			document,
-			meta_elements: Vec::new(),
+			elements: Vec::new(),
+			body_started: false,
 		}
 	}
 
@@ -141,47 +459,100 @@ impl HtmlSink {
 
 		node
 	}
+
+	/// Checks whether the node is a child of `<head>`, which is a child of `<html>`, which is a child of the document.
+	fn is_in_head_strict(&self, node: &Node) -> bool {
+		// Good *grief*, look at this mess. `if let … else` (or proper list comprehensions, like in Scala) would be *so* much nicer here.
+
+		// Does it have a parent?
+		if let Some(ancestor_head) = node.parent() {
+		// Is the parent an element?
+		if let Some(ancestor_head_element) = ancestor_head.as_element() {
+		// Is the parent element an HTML `<head>`?
+		if ancestor_head_element.name.expanded() == expanded_name!(html "head") {
+		// Does `<head>` have a parent?
+		if let Some(ancestor_html) = ancestor_head.parent() {
+		// Is `<head>`'s parent an element?
+		if let Some(ancestor_html_element) = ancestor_html.as_element() {
+		// Is `<head>`'s parent element `<html>`?
+		if ancestor_html_element.name.expanded() == expanded_name!(html "html") {
+		// Does `<html>` have a parent?
+		if let Some(ancestor_document) = ancestor_html.parent() {
+		// Is `<html>`'s parent the document?
+		return Rc::ptr_eq(&ancestor_document, &self.document);
+		}}}}}}}
+
+		false
+	}
+
+	/// Checks whether the node is part of the document (as opposed to, say, the contents of a `<template>`), and, if `in_head` is true, whether it has a `<head>` element among its ancestors.
+	fn is_in_document(&self, node: &Node, in_head: bool) -> bool {
+		let mut found_head = false;
+		let mut ancestor = node.parent();
+
+		while let Some(node) = ancestor {
+			if Rc::ptr_eq(&node, &self.document) {
+				return found_head || !in_head;
+			}
+
+			if node.as_element().is_some_and(|element| element.name.expanded() == expanded_name!(html "head")) {
+				found_head = true;
+			}
+
+			ancestor = node.parent();
+		}
+
+		false
+	}
 }
 
-impl html5ever::interface::TreeSink for HtmlSink {
+impl html5ever::interface::TreeSink for HtmlSink<'_> {
 	type Handle = Rc<Node>;
 	type Output = HtmlMeta;
 
 	fn finish(self) -> Self::Output {
-		let mut result = HtmlMeta {
-			no_index: false,
-			#[cfg(debug_assertions)]
-			all_nodes: self.all_nodes,
-		};
-
-		// Good *grief*, look at this mess. `if let … else` (or proper list comprehensions, like in Scala) would be *so* much nicer here.
+		let mut result = HtmlMeta::new();
+
+		// For every `<meta>`, `<link>`, and `<title>` element…
+		for node in &self.elements {
+			let element = node.as_element().expect("`self.elements` contains a non-element");
+
+			if element.name.expanded() == expanded_name!(html "meta") {
+				let content: Option<String> = element.content_attr.take();
+
+				// Does it have both a `name` and a `content`?
+				if let (Some(name), Some(content)) = (element.name_attr.take(), content.clone()) {
+					let position_ok: bool = match self.cfg.meta_robots_position {
+						MetaRobotsPosition::Strict => self.is_in_head_strict(node),
+						MetaRobotsPosition::HeadAnywhere => self.is_in_document(node, true),
+						MetaRobotsPosition::Anywhere => self.is_in_document(node, false),
+					};
+
+					result.add_meta(self.cfg, name, content, position_ok);
+				}
+				// Or is it `<meta http-equiv=refresh>`? Web browsers obey these anywhere in the document.
+				else if let (Some(http_equiv), Some(content)) = (element.http_equiv_attr.take(), content) {
+				if http_equiv == "refresh" && result.redirect.is_none() && self.is_in_document(node, false) {
+					result.redirect = MetaRefresh::parse(content);
+				}}
+			}
+			else if element.name.expanded() == expanded_name!(html "link") {
+				if result.canonical.is_none() && element.is_link_canonical.get() && self.is_in_document(node, true) {
+					result.canonical = element.href_attr.take();
+				}
+			}
+			else if element.name.expanded() == expanded_name!(html "title") {
+				if result.title.is_none() && self.is_in_document(node, false) {
+					result.title = Some(collapse_whitespace(&element.text.borrow()));
+				}
+			}
+			else {
+				panic!("`self.elements` contains an unexpected element: {:?}", element.name);
+			}
+		}
 
-		// For every node that we've collected so far that is a `<meta>` element…
-		for node in self.meta_elements {
-			// Make sure it really is an HTML `<meta>` element. This should always check out, so panic if it doesn't.
-			let element = node.as_element().expect("`self.meta_elements` contains a non-element");
-			assert_eq!(element.name.expanded(), expanded_name!(html "meta"), "`self.meta_elements` contains an element that is not HTML `<meta>`");
-
-			// Is this `<meta name=robots content=noindex>`?
-			if element.is_meta_name_robots.get() && element.is_meta_content_noindex.get() {
-			// Does it have a parent?
-			if let Some(ancestor_head) = node.parent() {
-			// Is the parent an element?
-			if let Some(ancestor_head_element) = ancestor_head.as_element() {
-			// Is the parent element an HTML `<head>`?
-			if ancestor_head_element.name.expanded() == expanded_name!(html "head") {
-			// Does `<head>` have a parent?
-			if let Some(ancestor_html) = ancestor_head.parent() {
-			// Is `<head>`'s parent an element?
-			if let Some(ancestor_html_element) = ancestor_html.as_element() {
-			// Is `<head>`'s parent element `<html>`?
-			if ancestor_html_element.name.expanded() == expanded_name!(html "html") {
-			// Does `<html>` have a parent?
-			if let Some(ancestor_document) = ancestor_html.parent() {
-			// Is `<html>`'s parent the document?
-			if Rc::ptr_eq(&ancestor_document, &self.document) {
-				result.no_index = true;
-			}}}}}}}}}
+		#[cfg(debug_assertions)] {
+			result.all_nodes = self.all_nodes;
 		}
 
 		result
@@ -205,20 +576,32 @@ impl html5ever::interface::TreeSink for HtmlSink {
 		attrs: Vec<html5ever::Attribute>,
 		_flags: html5ever::tree_builder::ElementFlags,
 	) -> Self::Handle {
-		let is_meta: bool = name.expanded() == expanded_name!(html "meta");
+		let is_interesting: bool = [
+			expanded_name!(html "meta"),
+			expanded_name!(html "link"),
+			expanded_name!(html "title"),
+		].contains(&name.expanded());
+
+		if name.expanded() == expanded_name!(html "body") || name.expanded() == expanded_name!(html "frameset") {
+			self.body_started = true;
+		}
 
 		let element = self.new_node(Node::new(NodeKind::Element(Element {
 			name,
-			is_meta_name_robots: Cell::new(false),
-			is_meta_content_noindex: Cell::new(false),
+			name_attr: RefCell::new(None),
+			content_attr: RefCell::new(None),
+			http_equiv_attr: RefCell::new(None),
+			is_link_canonical: Cell::new(false),
+			href_attr: RefCell::new(None),
+			text: RefCell::new(String::new()),
 		})));
 
 		for attr in &attrs {
 			element.set_attr(attr);
 		}
 
-		if is_meta {
-			self.meta_elements.push(Rc::clone(&element));
+		if is_interesting {
+			self.elements.push(Rc::clone(&element));
 		}
 
 		element
@@ -237,8 +620,18 @@ impl html5ever::interface::TreeSink for HtmlSink {
 		parent: &Self::Handle,
 		child: html5ever::tree_builder::NodeOrText<Self::Handle>,
 	) {
-		if let html5ever::tree_builder::NodeOrText::AppendNode(node) = child {
-			node.parent.replace(Some(Rc::clone(parent)));
+		match child {
+			html5ever::tree_builder::NodeOrText::AppendNode(node) => {
+				node.parent.replace(Some(Rc::clone(parent)));
+			}
+
+			// Keep the text of `<title>` elements.
+			html5ever::tree_builder::NodeOrText::AppendText(text) => {
+				if let Some(element) = parent.as_element() {
+				if element.name.expanded() == expanded_name!(html "title") {
+					element.text.borrow_mut().push_str(&text);
+				}}
+			}
 		}
 	}
 
@@ -294,3 +687,70 @@ impl html5ever::interface::TreeSink for HtmlSink {
 		node.parent.replace(Some(Rc::clone(new_parent)));
 	}
 }
+
+impl MetaRefresh {
+	/// Parses the `content` of a `<meta http-equiv=refresh>` element, like `5; url=/new/`, following the [algorithm](https://html.spec.whatwg.org/multipage/semantics.html#shared-declarative-refresh-steps) in the HTML standard. The delay is ignored.
+	///
+	/// The return value is `None` if the `content` is invalid or doesn't have a URL (in which case it just reloads the page).
+	fn parse(content: String) -> Option<Self> {
+		let is_space = |c: char| c.is_ascii_whitespace();
+
+		// The delay, which must start with a digit or `.`.
+		let rest = content.trim_start_matches(is_space);
+
+		if !rest.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+			return None;
+		}
+
+		let rest = rest.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+
+		// The delay must be followed by a separator.
+		if !rest.starts_with(|c: char| is_space(c) || c == ';' || c == ',') {
+			return None;
+		}
+
+		let rest = rest.trim_start_matches(is_space);
+		let rest = rest.strip_prefix([';', ',']).unwrap_or(rest);
+		let mut rest = rest.trim_start_matches(is_space);
+
+		// The URL may be preceded by `url=`.
+		if rest.get(..3).is_some_and(|prefix| prefix.eq_ignore_ascii_case("url")) {
+		if let Some(after_equals) = rest[3..].trim_start_matches(is_space).strip_prefix('=') {
+			rest = after_equals.trim_start_matches(is_space);
+		}}
+
+		// The URL may be quoted.
+		let url: &str = match rest.chars().next() {
+			Some(quote @ ('"' | '\'')) => {
+				let rest = &rest[1..];
+				rest.split(quote).next().unwrap_or(rest)
+			}
+
+			_ => rest,
+		};
+
+		let url = url.trim_end_matches(is_space);
+
+		if url.is_empty() {
+			return None;
+		}
+
+		Some(Self {
+			url: url.to_owned(),
+			content,
+		})
+	}
+}
+
+/// Checks whether a `rel` attribute value, which is a space-separated list of link types, contains `canonical`.
+fn is_canonical(rel: &str) -> bool {
+	rel.split_ascii_whitespace()
+	.any(|link_type| link_type.eq_ignore_ascii_case("canonical"))
+}
+
+/// Strips leading and trailing whitespace, and replaces each run of whitespace with a single space, like the `document.title` property in web browsers.
+pub(super) fn collapse_whitespace(text: &str) -> String {
+	text.split_ascii_whitespace()
+	.collect::<Vec<&str>>()
+	.join(" ")
+}
@@ -0,0 +1,203 @@
+use crate::config::Config;
+use lopdf::{Document, Object, ObjectId, Reader};
+use std::fmt::{self, Display};
+use super::check_html_meta::collapse_whitespace;
+
+/// The namespace of the `rdf:about` and similar attributes in XMP metadata, which aren't properties.
+const RDF_NS: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+
+/// Metadata read from a PDF document.
+pub struct PdfMeta {
+	/// The first [`Config::pdf_noindex_key`] entry whose value contains [`Config::pdf_noindex_value`], if any.
+	pub no_index: Option<PdfNoIndex>,
+}
+
+/// A metadata entry that says not to index a PDF document. Its `Display` implementation explains why the document is excluded.
+pub struct PdfNoIndex {
+	pub place: PdfMetaPlace,
+
+	/// The key, as written in the document. For XMP metadata, this includes the namespace prefix, if any.
+	pub key: String,
+
+	/// The whole value, with whitespace collapsed.
+	pub value: String,
+}
+
+/// Where in a PDF document a [`PdfNoIndex`] was found.
+pub enum PdfMetaPlace {
+	/// The document information dictionary (the `/Info` entry in the trailer).
+	Info,
+
+	/// The XMP metadata stream (the `/Metadata` entry in the document catalog).
+	Xmp,
+}
+
+impl PdfMeta {
+	/// Parses a PDF document and checks its document information dictionary, then its XMP metadata, for [`Config::pdf_noindex_key`].
+	///
+	/// Every object in the document still has to be parsed, because the document's cross-reference table may be compressed into a stream, but the contents of streams other than the XMP metadata (page contents, images, fonts, and so on) are thrown away as soon as they're read, so they don't all have to be kept in memory at once.
+	///
+	/// Encrypted documents are decrypted with an empty password, which is all that's needed for documents that can be opened without one. Other encrypted documents are an error.
+	pub fn parse(buffer: &[u8], cfg: &Config) -> lopdf::Result<Self> {
+		let mut doc = Reader {
+			buffer,
+			document: Document::new(),
+		}
+		.read(Some(drop_unneeded_streams))?;
+
+		if doc.is_encrypted() {
+			doc.decrypt("")?;
+		}
+
+		let no_index =
+			check_info(&doc, cfg)
+			.or_else(|| check_xmp(&doc, cfg));
+
+		Ok(Self { no_index })
+	}
+}
+
+/// Used with [`Reader::read`] to replace streams other than the XMP metadata and object streams (which hold other objects) with `null`, as they're read.
+///
+/// The returned object is only used for objects inside object streams, which are never streams themselves.
+fn drop_unneeded_streams(id: ObjectId, object: &mut Object) -> Option<(ObjectId, Object)> {
+	if let Object::Stream(stream) = object {
+		if !stream.dict.type_is(b"Metadata") && !stream.dict.type_is(b"ObjStm") {
+			*object = Object::Null;
+		}
+
+		return Some((id, Object::Null));
+	}
+
+	Some((id, object.clone()))
+}
+
+/// Checks the document information dictionary.
+fn check_info(doc: &Document, cfg: &Config) -> Option<PdfNoIndex> {
+	let info = doc.trailer.get_deref(b"Info", doc).ok()?.as_dict().ok()?;
+
+	info.iter().find_map(|(key, value)| {
+		if !key.eq_ignore_ascii_case(cfg.pdf_noindex_key.as_bytes()) {
+			return None;
+		}
+
+		let value: String = match doc.dereference(value).ok()?.1 {
+			Object::String(bytes, _) => decode_text_string(bytes),
+			Object::Name(name) => String::from_utf8_lossy(name).into_owned(),
+			_ => return None,
+		};
+
+		has_noindex_value(&value, cfg).then(|| PdfNoIndex {
+			place: PdfMetaPlace::Info,
+			key: String::from_utf8_lossy(key).into_owned(),
+			value: collapse_whitespace(&value),
+		})
+	})
+}
+
+/// Checks the XMP metadata. A property can be written either as an element (`<pdfx:Robots>noindex</pdfx:Robots>`) or as an attribute of `rdf:Description` (`pdfx:Robots="noindex"`). Namespaces are ignored; only the local name is compared with [`Config::pdf_noindex_key`].
+///
+/// XMP metadata that isn't well-formed XML is read up to the first error.
+fn check_xmp(doc: &Document, cfg: &Config) -> Option<PdfNoIndex> {
+	use xml::reader::{EventReader, XmlEvent};
+
+	let metadata = doc.catalog().ok()?.get_deref(b"Metadata", doc).ok()?.as_stream().ok()?;
+
+	let xml: Vec<u8> =
+		metadata.decompressed_content()
+		.unwrap_or_else(|_| metadata.content.clone());
+
+	let is_key = |name: &xml::name::OwnedName| -> bool {
+		name.local_name.eq_ignore_ascii_case(&cfg.pdf_noindex_key)
+		&& name.namespace.as_deref() != Some(RDF_NS)
+	};
+
+	let qualified_name = |name: &xml::name::OwnedName| -> String {
+		match &name.prefix {
+			Some(prefix) => format!("{prefix}:{}", name.local_name),
+			None => name.local_name.clone(),
+		}
+	};
+
+	// The property element being read, and its text so far. The text of nested elements (like the `rdf:li` items of an `rdf:Bag`) is included, separated by spaces.
+	let mut property: Option<(String, String)> = None;
+	let mut depth: usize = 0;
+
+	for event in EventReader::new(xml.as_slice()) {
+		match event.ok()? {
+			XmlEvent::StartElement { name, attributes, .. } => {
+				if let Some((_, text)) = &mut property {
+					depth += 1;
+					text.push(' ');
+				}
+				else if is_key(&name) {
+					property = Some((qualified_name(&name), String::new()));
+					depth = 0;
+				}
+
+				if let Some(attr) = attributes.iter().find(|attr| is_key(&attr.name) && has_noindex_value(&attr.value, cfg)) {
+					return Some(PdfNoIndex {
+						place: PdfMetaPlace::Xmp,
+						key: qualified_name(&attr.name),
+						value: collapse_whitespace(&attr.value),
+					});
+				}
+			}
+
+			XmlEvent::Characters(chars) | XmlEvent::CData(chars) => {
+				if let Some((_, text)) = &mut property {
+					text.push_str(&chars);
+				}
+			}
+
+			XmlEvent::EndElement { .. } if depth > 0 => {
+				depth -= 1;
+			}
+
+			XmlEvent::EndElement { .. } => {
+				if let Some((key, text)) = property.take() {
+				if has_noindex_value(&text, cfg) {
+					return Some(PdfNoIndex {
+						place: PdfMetaPlace::Xmp,
+						key,
+						value: collapse_whitespace(&text),
+					});
+				}}
+			}
+
+			XmlEvent::EndDocument => break,
+
+			_ => {}
+		}
+	}
+
+	None
+}
+
+/// Checks whether a metadata value contains [`Config::pdf_noindex_value`], as one of a list of values separated by commas or whitespace (like `noindex, nofollow`).
+fn has_noindex_value(value: &str, cfg: &Config) -> bool {
+	value
+	.split(|c: char| c == ',' || c.is_whitespace())
+	.any(|token| token.eq_ignore_ascii_case(&cfg.pdf_noindex_value))
+}
+
+/// Decodes a PDF text string, which is in UTF-16BE or UTF-8 if it starts with a byte order mark, or PDFDocEncoding otherwise. PDFDocEncoding is close enough to `windows-1252` for this purpose.
+fn decode_text_string(bytes: &[u8]) -> String {
+	let encoding = match encoding_rs::Encoding::for_bom(bytes) {
+		Some((encoding, _)) => encoding,
+		None => encoding_rs::WINDOWS_1252,
+	};
+
+	encoding.decode_with_bom_removal(bytes).0.into_owned()
+}
+
+impl Display for PdfNoIndex {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let Self { place, key, value } = self;
+
+		match place {
+			PdfMetaPlace::Info => write!(f, "It's a PDF document whose document information has `/{key}` set to `{value}`."),
+			PdfMetaPlace::Xmp => write!(f, "It's a PDF document whose XMP metadata has `{key}` set to `{value}`."),
+		}
+	}
+}
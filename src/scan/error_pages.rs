@@ -0,0 +1,89 @@
+use anyhow::Context as _;
+use crate::config::Config;
+use regex::{Regex, RegexBuilder};
+use std::fmt::{self, Display};
+use super::check_html_meta::HtmlMeta;
+
+/// Recognizes error pages (like `404.html`) and offline fallback pages, which don't belong in a sitemap, according to [`Config::error_page_file_names`] and [`Config::error_page_titles`].
+pub struct ErrorPages {
+	file_names: Vec<Regex>,
+	titles: Vec<Regex>,
+}
+
+impl ErrorPages {
+	pub fn new(cfg: &Config) -> anyhow::Result<Self> {
+		let compile = |setting: &str, patterns: &[String]| -> anyhow::Result<Vec<Regex>> {
+			patterns.iter()
+			.map(|pattern| {
+				RegexBuilder::new(pattern)
+				.case_insensitive(true)
+				.build()
+				.with_context(|| format!("`{pattern}` in `{setting}` is not a valid regex"))
+			})
+			.collect()
+		};
+
+		Ok(Self {
+			file_names: compile("error_page_file_names", &cfg.error_page_file_names)?,
+			titles: compile("error_page_titles", &cfg.error_page_titles)?,
+		})
+	}
+
+	/// Checks whether a file's name (without the folder it's in) looks like an error page.
+	pub fn check_file_name<'e>(&'e self, file_name: &str) -> Option<ErrorPage<'e>> {
+		self.file_names.iter()
+		.find(|regex| regex.is_match(file_name))
+		.map(|regex| ErrorPage::FileName {
+			file_name: file_name.to_owned(),
+			pattern: regex.as_str(),
+		})
+	}
+
+	/// Checks whether an HTML page's title or `<meta>` elements say that it's an error page.
+	pub fn check_html<'e>(&'e self, html_meta: &HtmlMeta) -> Option<ErrorPage<'e>> {
+		if let Some(status_code) = &html_meta.status_code {
+		if status_code.status_code.is_some_and(|code| !(200..300).contains(&code)) {
+			return Some(ErrorPage::StatusCode {
+				name: status_code.name.clone(),
+				content: status_code.content.clone(),
+			});
+		}}
+
+		let title = html_meta.title.as_deref()?;
+
+		self.titles.iter()
+		.find(|regex| regex.is_match(title))
+		.map(|regex| ErrorPage::Title {
+			title: title.to_owned(),
+			pattern: regex.as_str(),
+		})
+	}
+}
+
+/// The result of [`ErrorPages::check_file_name`] or [`ErrorPages::check_html`] for an error page. Its `Display` implementation explains why it's considered an error page.
+pub enum ErrorPage<'e> {
+	FileName {
+		file_name: String,
+		pattern: &'e str,
+	},
+
+	Title {
+		title: String,
+		pattern: &'e str,
+	},
+
+	StatusCode {
+		name: String,
+		content: String,
+	},
+}
+
+impl Display for ErrorPage<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::FileName { file_name, pattern } => write!(f, "Its file name, `{file_name}`, looks like an error page (it matches `{pattern}` in `error_page_file_names`)."),
+			Self::Title { title, pattern } => write!(f, "It's an HTML page whose title, `{title}`, looks like an error page (it matches `{pattern}` in `error_page_titles`)."),
+			Self::StatusCode { name, content } => write!(f, "It's an HTML page with `<meta name={name} content=\"{content}\">`, so it's served as an error page."),
+		}
+	}
+}
@@ -0,0 +1,77 @@
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::path::PathBuf;
+use super::robots_directives::parse_date;
+
+/// A file named in a file list, to be considered for the sitemap instead of scanning the `root_dir`.
+pub struct FileListEntry {
+	/// The path of the file, relative to the `root_dir` unless it's absolute.
+	pub path: PathBuf,
+
+	/// The file's last modification time, if the file list says.
+	pub lastmod: Option<DateTime<Utc>>,
+}
+
+/// An entry in a JSON manifest: either just a path, or an object with a path and other information.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ManifestEntry {
+	Path(PathBuf),
+	File(ManifestFile),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestFile {
+	path: PathBuf,
+	lastmod: Option<String>,
+}
+
+/// Parses a file list. It's either a JSON manifest, if it starts with `[`, or else a plain list of paths, one per line.
+///
+/// A JSON manifest is an array whose items are either paths, like `"index.html"`, or objects with a path and, optionally, a last modification time, like `{"path": "index.html", "lastmod": "2024-06-30T12:00:00Z"}`.
+///
+/// In a plain list, blank lines are ignored. Whitespace at the beginning and end of each line is not, since it could be part of the file name, except for the `\r` of a Windows-style line ending.
+pub fn parse(text: &str) -> anyhow::Result<Vec<FileListEntry>> {
+	let text = text.trim_start_matches('\u{FEFF}');
+
+	if !text.trim_start().starts_with('[') {
+		return Ok(
+			text.lines()
+			.map(|line| line.strip_suffix('\r').unwrap_or(line))
+			.filter(|line| !line.is_empty())
+			.map(|line| FileListEntry {
+				path: PathBuf::from(line),
+				lastmod: None,
+			})
+			.collect()
+		);
+	}
+
+	let manifest: Vec<ManifestEntry> =
+		serde_json::from_str(text)
+		.context("the JSON manifest is invalid")?;
+
+	manifest.into_iter()
+	.map(|entry| match entry {
+		ManifestEntry::Path(path) => Ok(FileListEntry {
+			path,
+			lastmod: None,
+		}),
+
+		ManifestEntry::File(ManifestFile { path, lastmod }) => {
+			let lastmod: Option<DateTime<Utc>> = match lastmod {
+				Some(lastmod) => Some(
+					parse_date(lastmod.trim())
+					.with_context(|| format!("the `lastmod` of `{}` in the JSON manifest, `{lastmod}`, is not a date that can be understood", path.display()))?
+				),
+
+				None => None,
+			};
+
+			Ok(FileListEntry { path, lastmod })
+		}
+	})
+	.collect()
+}
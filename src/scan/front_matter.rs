@@ -0,0 +1,148 @@
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use std::io::{self, BufRead};
+use super::robots_directives::parse_date;
+
+/// The fields of a Markdown file's front matter that affect the sitemap, as used by static site generators like Hugo, Jekyll, and Zola.
+#[derive(Default)]
+pub struct FrontMatter {
+	/// `draft: true`
+	pub draft: bool,
+
+	/// `sitemap: false`, or `sitemap: { disable: true }` as in Hugo.
+	pub sitemap_disabled: bool,
+
+	/// `lastmod: …`, in any of the formats accepted for `unavailable_after` robots directives.
+	pub lastmod: Option<DateTime<Utc>>,
+
+	/// `url: …`, the URL-path that the page will be published at, relative to the `root_url`.
+	pub url: Option<String>,
+}
+
+/// The lines that start and end front matter: `---` for YAML, `+++` for TOML.
+enum Delimiter {
+	Yaml,
+	Toml,
+}
+
+impl FrontMatter {
+	/// Reads the front matter at the beginning of a file. A file without front matter has the default, empty front matter.
+	///
+	/// Only the front matter is read, not the rest of the file. Front matter that can't be parsed is an error.
+	pub fn read(input: impl BufRead) -> anyhow::Result<Self> {
+		let mut lines = input.lines();
+
+		let delimiter: Delimiter = match lines.next().transpose()? {
+			Some(line) => match line.trim_start_matches('\u{FEFF}').trim_end() {
+				"---" => Delimiter::Yaml,
+				"+++" => Delimiter::Toml,
+				_ => return Ok(Self::default()),
+			},
+
+			None => return Ok(Self::default()),
+		};
+
+		let end: &str = match delimiter {
+			Delimiter::Yaml => "---",
+			Delimiter::Toml => "+++",
+		};
+
+		let mut text = String::new();
+
+		loop {
+			let line: String =
+				lines.next()
+				.ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))
+				.with_context(|| format!("the front matter doesn't end with a `{end}` line"))??;
+
+			// YAML documents can also end with `...`.
+			if line.trim_end() == end || matches!(delimiter, Delimiter::Yaml) && line.trim_end() == "..." {
+				break;
+			}
+
+			text.push_str(&line);
+			text.push('\n');
+		}
+
+		match delimiter {
+			Delimiter::Yaml => {
+				let documents: Vec<yaml_rust2::Yaml> =
+					yaml_rust2::YamlLoader::load_from_str(&text)
+					.context("the YAML front matter is invalid")?;
+
+				// Empty front matter has no documents at all.
+				match documents.first() {
+					Some(value) => Self::from_yaml(value),
+					None => Ok(Self::default()),
+				}
+			}
+
+			Delimiter::Toml => {
+				let value: toml::Value =
+					toml::from_str(&text)
+					.context("the TOML front matter is invalid")?;
+
+				Self::from_toml(&value)
+			}
+		}
+	}
+
+	/// Indexing a `Yaml` value with a key that it doesn't have, or that isn't a mapping, gives `Yaml::BadValue`, so that's treated the same as a missing field.
+	fn from_yaml(value: &yaml_rust2::Yaml) -> anyhow::Result<Self> {
+		use yaml_rust2::Yaml;
+
+		Ok(Self {
+			draft: matches!(value["draft"], Yaml::Boolean(true)),
+
+			sitemap_disabled: match &value["sitemap"] {
+				Yaml::Boolean(false) => true,
+				sitemap @ Yaml::Hash(_) => matches!(sitemap["disable"], Yaml::Boolean(true)),
+				_ => false,
+			},
+
+			lastmod: match &value["lastmod"] {
+				Yaml::String(lastmod) => Some(parse_lastmod(lastmod)?),
+				Yaml::Null | Yaml::BadValue => None,
+				_ => anyhow::bail!("`lastmod` in the front matter is not a date"),
+			},
+
+			url: match &value["url"] {
+				Yaml::String(url) => Some(url.clone()),
+				Yaml::Null | Yaml::BadValue => None,
+				_ => anyhow::bail!("`url` in the front matter is not a string"),
+			},
+		})
+	}
+
+	fn from_toml(value: &toml::Value) -> anyhow::Result<Self> {
+		use toml::Value;
+
+		Ok(Self {
+			draft: matches!(value.get("draft"), Some(Value::Boolean(true))),
+
+			sitemap_disabled: match value.get("sitemap") {
+				Some(Value::Boolean(false)) => true,
+				Some(sitemap @ Value::Table(_)) => matches!(sitemap.get("disable"), Some(Value::Boolean(true))),
+				_ => false,
+			},
+
+			lastmod: match value.get("lastmod") {
+				Some(Value::String(lastmod)) => Some(parse_lastmod(lastmod)?),
+				Some(Value::Datetime(lastmod)) => Some(parse_lastmod(&lastmod.to_string())?),
+				None => None,
+				Some(_) => anyhow::bail!("`lastmod` in the front matter is not a date"),
+			},
+
+			url: match value.get("url") {
+				Some(Value::String(url)) => Some(url.clone()),
+				None => None,
+				Some(_) => anyhow::bail!("`url` in the front matter is not a string"),
+			},
+		})
+	}
+}
+
+fn parse_lastmod(lastmod: &str) -> anyhow::Result<DateTime<Utc>> {
+	parse_date(lastmod.trim())
+	.with_context(|| format!("`lastmod` in the front matter, `{lastmod}`, is not a date that can be understood"))
+}
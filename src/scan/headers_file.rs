@@ -0,0 +1,141 @@
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use std::fmt::{self, Display};
+use super::robots_directives::RobotsDirectives;
+use url::Url;
+
+/// A parsed `_headers` file, in the format used by static web hosts like Netlify and Cloudflare Pages. Only `X-Robots-Tag` headers are kept.
+///
+/// The file consists of URL patterns, each followed by indented `Name: value` header lines:
+///
+/// ```text
+/// /drafts/*
+///   X-Robots-Tag: noindex
+/// ```
+pub struct HeadersFile {
+	blocks: Vec<Block>,
+
+	/// The lines that weren't recognized, and were skipped, with their line numbers (starting at 1). These include header lines before any URL pattern, and header lines without a `:`, like Cloudflare Pages' `! Name` lines for removing a header.
+	pub unrecognized_lines: Vec<(usize, String)>,
+}
+
+/// A URL pattern and the `X-Robots-Tag` headers it applies.
+struct Block {
+	/// The pattern as written in the file.
+	pattern: String,
+
+	/// The pattern, compiled to a regex.
+	regex: Regex,
+
+	/// Whether the pattern is an absolute URL. If not, it is matched against only the path part of the URL.
+	is_absolute: bool,
+
+	/// The values of the `X-Robots-Tag` headers in this block.
+	x_robots_tags: Vec<String>,
+}
+
+impl HeadersFile {
+	pub fn parse(text: &str) -> Self {
+		let mut blocks: Vec<Block> = Vec::new();
+		let mut unrecognized_lines: Vec<(usize, String)> = Vec::new();
+
+		for (line_index, line) in text.lines().enumerate() {
+			let trimmed = line.trim();
+
+			if trimmed.is_empty() || trimmed.starts_with('#') {
+				continue;
+			}
+
+			// An indented line is a header. Otherwise, it's a URL pattern.
+			if line.starts_with(char::is_whitespace) {
+				let (Some(block), Some((name, value))) = (blocks.last_mut(), trimmed.split_once(':')) else {
+					unrecognized_lines.push((line_index + 1, trimmed.to_owned()));
+					continue;
+				};
+
+				if name.trim().eq_ignore_ascii_case("X-Robots-Tag") {
+					block.x_robots_tags.push(value.trim().to_owned());
+				}
+			}
+			else {
+				let is_absolute = trimmed.contains("://");
+
+				blocks.push(Block {
+					pattern: trimmed.to_owned(),
+					regex: pattern_to_regex(trimmed),
+					is_absolute,
+					x_robots_tags: Vec::new(),
+				});
+			}
+		}
+
+		blocks.retain(|block| !block.x_robots_tags.is_empty());
+
+		Self { blocks, unrecognized_lines }
+	}
+
+	/// Checks whether the headers file gives the URL an `X-Robots-Tag` header that says not to index it at the given time, for one of the given bots. That is the case if the header has `noindex` or `none`, or an `unavailable_after` date that has passed.
+	///
+	/// A header without a user-agent prefix, like `X-Robots-Tag: noindex`, is treated as being for the bot named `robots`, same as `<meta name=robots>`. One with a prefix, like `X-Robots-Tag: googlebot: noindex`, is for the named bot only.
+	///
+	/// The return value is `None` if the URL is not excluded, or `Some` if it is.
+	pub fn check<'h>(&'h self, url: &Url, agents: &[String], now: DateTime<Utc>) -> Option<HeadersExclusion<'h>> {
+		self.blocks.iter()
+		.filter(|block| {
+			if block.is_absolute {
+				block.regex.is_match(url.as_str())
+			}
+			else {
+				block.regex.is_match(url.path())
+			}
+		})
+		.flat_map(|block| block.x_robots_tags.iter().map(move |value| (block, value)))
+		.find(|(_, value)| {
+			let (agent, directives) = super::robots_directives::split_x_robots_tag(value);
+			let agent = agent.unwrap_or("robots");
+
+			agents.iter().any(|a| a.eq_ignore_ascii_case(agent))
+			&& RobotsDirectives::parse(directives).excludes(now)
+		})
+		.map(|(block, value)| HeadersExclusion {
+			pattern: block.pattern.as_str(),
+			x_robots_tag: value.as_str(),
+		})
+	}
+}
+
+/// Converts a URL pattern into an anchored regex. `*` matches anything (including `/`), and a `:placeholder` matches a single path segment. Everything else is matched literally.
+fn pattern_to_regex(pattern: &str) -> Regex {
+	let mut regex = String::from("^");
+	let mut chars = pattern.char_indices().peekable();
+
+	while let Some((index, c)) = chars.next() {
+		match c {
+			'*' => regex.push_str(".*"),
+
+			':' if pattern[index + 1..].starts_with(|c: char| c.is_ascii_alphabetic()) => {
+				while chars.next_if(|(_, c)| c.is_ascii_alphanumeric() || *c == '_').is_some() {}
+				regex.push_str("[^/]+");
+			}
+
+			_ => regex.push_str(regex::escape(c.encode_utf8(&mut [0; 4])).as_str()),
+		}
+	}
+
+	regex.push('$');
+
+	Regex::new(regex.as_str())
+	.expect("regex generated from a `_headers` URL pattern is invalid")
+}
+
+/// The result of [`HeadersFile::check`] for a URL that the headers file excludes.
+pub struct HeadersExclusion<'h> {
+	pattern: &'h str,
+	x_robots_tag: &'h str,
+}
+
+impl Display for HeadersExclusion<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "The headers file gives it `X-Robots-Tag: {}` (for the URL pattern `{}`).", self.x_robots_tag, self.pattern)
+	}
+}
@@ -0,0 +1,57 @@
+use anyhow::Context as _;
+use sitemap::{
+	reader::{SiteMapEntity, SiteMapReader},
+	structs::{LastMod, Location, Priority, UrlEntry},
+};
+use std::{
+	fs::File,
+	io::BufReader,
+	path::Path,
+};
+use url::Url;
+
+/// Reads the `<url>` entries of an existing sitemap, for [`Config::merge_sitemap_paths`](crate::config::Config::merge_sitemap_paths).
+///
+/// Every entry must have a valid `<loc>` that starts with the `root_url`, and valid `<lastmod>` and `<priority>`, if any. A sitemap index is an error, since its entries are sitemaps, not pages.
+pub fn read_sitemap(path: &Path, root_url: &Url) -> anyhow::Result<Vec<UrlEntry>> {
+	let fd =
+		File::open(path)
+		.with_context(|| format!("couldn't open sitemap `{}`", path.display()))?;
+
+	let mut entries: Vec<UrlEntry> = Vec::new();
+
+	for entity in SiteMapReader::new(BufReader::new(fd)) {
+		let entry: UrlEntry = match entity {
+			SiteMapEntity::Url(entry) => entry,
+			SiteMapEntity::SiteMap(_) => anyhow::bail!("`{}` is a sitemap index, not a sitemap; only sitemaps can be merged", path.display()),
+			SiteMapEntity::Err(error) => return Err(
+				anyhow::Error::new(error)
+				.context(format!("couldn't read sitemap `{}`", path.display()))
+			),
+		};
+
+		let loc: Url = match &entry.loc {
+			Location::Url(loc) => loc.clone(),
+			Location::None => anyhow::bail!("sitemap `{}` has a `<url>` without a `<loc>`", path.display()),
+			Location::ParseErr(error) => anyhow::bail!("sitemap `{}` has a `<loc>` that isn't a valid URL: {error}", path.display()),
+		};
+
+		anyhow::ensure!(
+			loc.as_str().starts_with(root_url.as_str()),
+			"sitemap `{}` has the URL `{loc}`, which does not start with the configured `root_url`, `{root_url}`, in violation of the sitemaps protocol",
+			path.display(),
+		);
+
+		if let LastMod::ParseErr(error) = &entry.lastmod {
+			anyhow::bail!("sitemap `{}` has a `<lastmod>` for `{loc}` that isn't a valid date: {error}", path.display());
+		}
+
+		if let Priority::ParseErr(error) = &entry.priority {
+			anyhow::bail!("sitemap `{}` has a `<priority>` for `{loc}` that isn't a valid number: {error}", path.display());
+		}
+
+		entries.push(entry);
+	}
+
+	Ok(entries)
+}
@@ -0,0 +1,174 @@
+use anyhow::Context as _;
+use crate::config::{Config, RobotsUserAgentsPolicy};
+use std::fmt::{self, Display};
+
+/// A parsed `robots.txt`, with rules for each of the configured [`Config::robots_user_agents`].
+pub struct Robots<'c> {
+	txt: Vec<u8>,
+	robots: Vec<(&'c str, texting_robots::Robot)>,
+	policy: RobotsUserAgentsPolicy,
+}
+
+impl<'c> Robots<'c> {
+	pub fn new(cfg: &'c Config, txt: Vec<u8>) -> anyhow::Result<Self> {
+		let robots =
+			cfg.robots_user_agents.iter()
+			.map(|agent| -> anyhow::Result<_> {
+				let robot =
+					texting_robots::Robot::new(agent, txt.as_slice())
+					.context("`robots.txt` is invalid")?;
+
+				Ok((agent.as_str(), robot))
+			})
+			.collect::<anyhow::Result<Vec<_>>>()?;
+
+		Ok(Self {
+			txt,
+			robots,
+			policy: cfg.robots_user_agents_policy,
+		})
+	}
+
+	/// Checks whether `robots.txt` allows the given URL-path, which must start with a slash.
+	///
+	/// The return value is `None` if the URL-path is allowed, or `Some` if it is excluded.
+	pub fn check<'r>(&'r self, url_path: &'r str) -> Option<RobotsExclusion<'r>> {
+		let mut disallowing_agents =
+			self.robots.iter()
+			.filter(|(_, robot)| !robot.allowed(url_path))
+			.map(|(agent, _)| *agent);
+
+		let excluded = match self.policy {
+			RobotsUserAgentsPolicy::Any => disallowing_agents.next().is_some(),
+			RobotsUserAgentsPolicy::All => disallowing_agents.count() == self.robots.len(),
+		};
+
+		if excluded {
+			Some(RobotsExclusion { robots: self, url_path })
+		}
+		else {
+			None
+		}
+	}
+}
+
+/// The result of [`Robots::check`] for a URL-path that `robots.txt` excludes. Its `Display` implementation explains which user-agents were disallowed, and by which directives.
+pub struct RobotsExclusion<'r> {
+	robots: &'r Robots<'r>,
+	url_path: &'r str,
+}
+
+impl Display for RobotsExclusion<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "`robots.txt` says to exclude it")?;
+
+		let mut first = true;
+
+		for (agent, robot) in &self.robots.robots {
+			if robot.allowed(self.url_path) {
+				continue;
+			}
+
+			write!(f, "{}", if first { " for " } else { ", and for " })?;
+			first = false;
+
+			write!(f, "user-agent `{agent}`")?;
+
+			if let Some(directive) = find_directive(self.robots.txt.as_slice(), agent, self.url_path) {
+				write!(f, " (`{directive}`)")?;
+			}
+		}
+
+		write!(f, ".")
+	}
+}
+
+/// Finds the `Allow` or `Disallow` directive in `robots.txt` that decides whether the given user-agent may access the given URL-path.
+///
+/// `texting_robots` doesn't say which directive it used to make its decision, so this is a best-effort reconstruction, used only to explain exclusions. It groups directives by user-agent the same way `texting_robots` does, then uses `texting_robots` to check which directives match the URL-path, then picks the longest match (preferring `Allow` if there's a tie).
+fn find_directive(txt: &[u8], agent: &str, url_path: &str) -> Option<String> {
+	struct Group<'t> {
+		agents: Vec<String>,
+		directives: Vec<(bool, &'t str)>,
+	}
+
+	let txt = String::from_utf8_lossy(txt);
+	let mut groups: Vec<Group> = Vec::new();
+	let mut prev_line_was_agent = false;
+
+	for line in txt.lines() {
+		let line = line.split('#').next().unwrap_or_default();
+
+		let (key, value) = match line.split_once(':') {
+			Some(ok) => ok,
+			None => continue,
+		};
+
+		let key = key.trim().to_ascii_lowercase();
+		let value = value.trim();
+
+		match key.as_str() {
+			"user-agent" => {
+				if !prev_line_was_agent {
+					groups.push(Group {
+						agents: Vec::new(),
+						directives: Vec::new(),
+					});
+				}
+
+				groups.last_mut().unwrap().agents.push(value.to_lowercase());
+				prev_line_was_agent = true;
+				continue;
+			}
+
+			"allow" | "disallow" => {
+				// Directives before the first `User-agent` line go in a group with no user-agents. They only apply if there are no `User-agent` lines at all.
+				if groups.is_empty() {
+					groups.push(Group {
+						agents: Vec::new(),
+						directives: Vec::new(),
+					});
+				}
+
+				if !value.is_empty() {
+					groups.last_mut().unwrap().directives.push((key == "allow", value));
+				}
+			}
+
+			_ => {}
+		}
+
+		prev_line_was_agent = false;
+	}
+
+	// Use the groups for this user-agent, or the groups for `*` if none mention this user-agent, or all groups if there are no `User-agent` lines.
+	let agent = agent.to_lowercase();
+
+	let group_agent: Option<&str> =
+		if groups.iter().all(|group| group.agents.is_empty()) {
+			None
+		}
+		else if groups.iter().any(|group| group.agents.contains(&agent)) {
+			Some(agent.as_str())
+		}
+		else {
+			Some("*")
+		};
+
+	groups.iter()
+	.filter(|group| match group_agent {
+		Some(group_agent) => group.agents.iter().any(|agent| agent == group_agent),
+		None => true,
+	})
+	.flat_map(|group| group.directives.iter())
+	.filter(|(_, pattern)| {
+		// Check if this pattern matches the URL-path by making a `robots.txt` that disallows only this pattern.
+		texting_robots::Robot::new("*", format!("Disallow: {pattern}").as_bytes())
+		.is_ok_and(|robot| !robot.allowed(url_path))
+	})
+	.max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+	.map(|(allow, pattern)| format!(
+		"{}: {pattern}",
+		if *allow { "Allow" } else { "Disallow" },
+	))
+}
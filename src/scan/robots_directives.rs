@@ -0,0 +1,118 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone as _, Utc};
+
+/// Names of robots directives that take a value after a colon, like `max-snippet: 20`. These can't be confused with user-agent names in `X-Robots-Tag` headers.
+const DIRECTIVES_WITH_VALUES: &[&str] = &[
+	"unavailable_after",
+	"max-snippet",
+	"max-image-preview",
+	"max-video-preview",
+];
+
+/// A parsed list of robots directives, as found in `<meta name=robots content=…>` or an `X-Robots-Tag` header. Only the directives that matter for sitemaps are kept.
+#[derive(Debug, Default)]
+pub struct RobotsDirectives {
+	/// Whether the directives include `noindex` or `none`. (`none` means `noindex, nofollow`.)
+	pub no_index: bool,
+
+	/// Whether the directives include `nofollow` or `none`.
+	pub no_follow: bool,
+
+	/// The date given by an `unavailable_after` directive, after which the page should no longer be indexed. If the date can't be parsed, the directive is ignored, like search engines do.
+	pub unavailable_after: Option<DateTime<Utc>>,
+}
+
+impl RobotsDirectives {
+	/// Parses a comma-separated list of robots directives. Directive names are not case-sensitive, and unknown directives are ignored.
+	pub fn parse(directives: &str) -> Self {
+		let mut result = Self::default();
+		let mut parts = directives.split(',').map(str::trim);
+
+		while let Some(part) = parts.next() {
+			let (name, value) = match part.split_once(':') {
+				Some((name, value)) => (name.trim(), Some(value.trim())),
+				None => (part, None),
+			};
+
+			if name.eq_ignore_ascii_case("noindex") {
+				result.no_index = true;
+			}
+			else if name.eq_ignore_ascii_case("nofollow") {
+				result.no_follow = true;
+			}
+			else if name.eq_ignore_ascii_case("none") {
+				result.no_index = true;
+				result.no_follow = true;
+			}
+			else if name.eq_ignore_ascii_case("unavailable_after") {
+			if let Some(value) = value {
+				// Some date formats, like `Friday, 25-Jun-10 15:00:00 GMT`, contain a comma, so if the date doesn't parse, try again with the next part too.
+				result.unavailable_after =
+					parse_date(value)
+					.or_else(|| {
+						let next = parts.clone().next()?;
+						let date = parse_date(format!("{value}, {next}").as_str())?;
+						parts.next();
+						Some(date)
+					});
+			}}
+		}
+
+		result
+	}
+
+	/// Checks whether these directives say not to index the page at the given time.
+	pub fn excludes(&self, now: DateTime<Utc>) -> bool {
+		self.no_index || self.unavailable_after.is_some_and(|date| date <= now)
+	}
+}
+
+/// Parses the date of an `unavailable_after` directive. Like Google, this accepts RFC 822 (as updated by RFC 2822), RFC 850, and ISO 8601 dates. Dates without a time zone are taken to be in UTC.
+///
+/// This is also used for `lastmod` dates in front matter and file lists.
+pub(super) fn parse_date(date: &str) -> Option<DateTime<Utc>> {
+	// ISO 8601, with a time zone.
+	if let Ok(date) = DateTime::parse_from_rfc3339(date) {
+		return Some(date.with_timezone(&Utc));
+	}
+
+	// ISO 8601, without a time zone.
+	for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S"] {
+	if let Ok(date) = NaiveDateTime::parse_from_str(date, format) {
+		return Some(Utc.from_utc_datetime(&date));
+	}}
+
+	// ISO 8601, date only.
+	if let Ok(date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+		return Some(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0)?));
+	}
+
+	// RFC 822/2822, like `Fri, 25 Jun 2010 15:00:00 PST`.
+	if let Ok(date) = DateTime::parse_from_rfc2822(date) {
+		return Some(date.with_timezone(&Utc));
+	}
+
+	// RFC 850, like `Friday, 25-Jun-10 15:00:00 PST`. This is the same as RFC 822, except with the full day name and dashes in the date, so convert it.
+	let (_, rest) = date.split_once(',')?;
+	let rest = rest.trim_start();
+	let (day_month_year, time_zone) = rest.split_once(' ')?;
+
+	DateTime::parse_from_rfc2822(format!("{} {time_zone}", day_month_year.replace('-', " ")).as_str())
+	.ok()
+	.map(|date| date.with_timezone(&Utc))
+}
+
+/// Splits the value of an `X-Robots-Tag` header into the user-agent it's for (if any) and the list of directives.
+///
+/// An `X-Robots-Tag` header can start with a user-agent name and a colon, like `googlebot: noindex`, in which case it only applies to that user-agent. Otherwise, it applies to all user-agents.
+pub fn split_x_robots_tag(value: &str) -> (Option<&str>, &str) {
+	if let Some((prefix, rest)) = value.split_once(':') {
+		let prefix = prefix.trim();
+
+		// A directive with a value, like `unavailable_after: …`, is not a user-agent name. Neither is anything containing a comma, as in `noindex, max-snippet: 20`.
+		if !prefix.contains(',') && !DIRECTIVES_WITH_VALUES.iter().any(|directive| prefix.eq_ignore_ascii_case(directive)) {
+			return (Some(prefix), rest);
+		}
+	}
+
+	(None, value)
+}
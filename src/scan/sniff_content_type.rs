@@ -0,0 +1,109 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// How many bytes at the start of a file to examine. This is the size of the “resource header” in the WHATWG MIME Sniffing standard.
+const HEADER_LEN: u64 = 1445;
+
+/// Guesses the MIME type of a file by looking at its first few bytes, then rewinds the file to its beginning.
+///
+/// This is a subset of the [“rules for identifying an unknown MIME type”](https://mimesniff.spec.whatwg.org/#rules-for-identifying-an-unknown-mime-type) from the WHATWG MIME Sniffing standard, with the “sniff-scriptable” flag set. The return value is never empty; if nothing else matches, it is either `text/plain` or `application/octet-stream`.
+pub fn sniff_content_type(input: &mut (impl Read + Seek)) -> io::Result<&'static str> {
+	let mut header = Vec::new();
+	input.by_ref().take(HEADER_LEN).read_to_end(&mut header)?;
+	input.seek(SeekFrom::Start(0))?;
+
+	Ok(sniff_bytes(header.as_slice()))
+}
+
+fn sniff_bytes(header: &[u8]) -> &'static str {
+	// Markup. Leading whitespace is ignored, and tag names are matched case-insensitively.
+	{
+		let markup = &header[header.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(header.len())..];
+
+		for tag in HTML_TAGS {
+			if let Some(rest) = strip_prefix_ignore_ascii_case(markup, tag) {
+			if matches!(rest.first(), Some(b' ' | b'>')) {
+				return "text/html";
+			}}
+		}
+
+		if markup.starts_with(b"<?xml") {
+			return "text/xml";
+		}
+	}
+
+	// Byte order marks. These mean the file is text, but says nothing about what kind.
+	if header.starts_with(b"\xFE\xFF")
+	|| header.starts_with(b"\xFF\xFE")
+	|| header.starts_with(b"\xEF\xBB\xBF") {
+		return "text/plain";
+	}
+
+	// Everything else is identified by an exact signature at the very start of the file.
+	for (signature, content_type) in SIGNATURES {
+		if header.starts_with(signature) {
+			return content_type;
+		}
+	}
+
+	if header.len() >= 14 && header.starts_with(b"RIFF") && &header[8..14] == b"WEBPVP" {
+		return "image/webp";
+	}
+
+	if header.iter().any(|byte| is_binary_data_byte(*byte)) {
+		"application/octet-stream"
+	}
+	else {
+		"text/plain"
+	}
+}
+
+/// Markup that indicates HTML, if it appears at the start of a file and is followed by a space or `>`.
+const HTML_TAGS: &[&[u8]] = &[
+	b"<!DOCTYPE HTML",
+	b"<HTML",
+	b"<HEAD",
+	b"<SCRIPT",
+	b"<IFRAME",
+	b"<H1",
+	b"<DIV",
+	b"<FONT",
+	b"<TABLE",
+	b"<A",
+	b"<STYLE",
+	b"<TITLE",
+	b"<B",
+	b"<BODY",
+	b"<BR",
+	b"<P",
+	b"<!--",
+];
+
+const SIGNATURES: &[(&[u8], &str)] = &[
+	(b"%PDF-", "application/pdf"),
+	(b"%!PS-Adobe-", "application/postscript"),
+	(b"GIF87a", "image/gif"),
+	(b"GIF89a", "image/gif"),
+	(b"\x89PNG\r\n\x1A\n", "image/png"),
+	(b"\xFF\xD8\xFF", "image/jpeg"),
+	(b"BM", "image/bmp"),
+	(b"\x00\x00\x01\x00", "image/x-icon"),
+	(b"\x00\x00\x02\x00", "image/x-icon"),
+	(b"wOFF", "font/woff"),
+	(b"wOF2", "font/woff2"),
+	(b"\x1F\x8B\x08", "application/x-gzip"),
+	(b"PK\x03\x04", "application/zip"),
+	(b"Rar \x1A\x07\x00", "application/x-rar-compressed"),
+];
+
+fn strip_prefix_ignore_ascii_case<'a>(bytes: &'a [u8], prefix: &[u8]) -> Option<&'a [u8]> {
+	if bytes.len() >= prefix.len() && bytes[..prefix.len()].eq_ignore_ascii_case(prefix) {
+		Some(&bytes[prefix.len()..])
+	}
+	else {
+		None
+	}
+}
+
+fn is_binary_data_byte(byte: u8) -> bool {
+	matches!(byte, 0x00..=0x08 | 0x0B | 0x0E..=0x1A | 0x1C..=0x1F)
+}
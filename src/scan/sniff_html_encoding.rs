@@ -0,0 +1,248 @@
+use encoding_rs::Encoding;
+
+/// How many bytes at the start of an HTML file to examine for a `<meta charset>`. This is the limit recommended by the HTML standard.
+pub const PRESCAN_LEN: u64 = 1024;
+
+/// Figures out the character encoding of an HTML file, given its first [`PRESCAN_LEN`] bytes.
+///
+/// This is a subset of the [encoding sniffing algorithm](https://html.spec.whatwg.org/multipage/parsing.html#encoding-sniffing-algorithm) from the HTML standard: a byte order mark wins, then a `<meta charset>` or `<meta http-equiv=Content-Type>` element, then the `fallback`.
+pub fn sniff_html_encoding(prefix: &[u8], fallback: &'static Encoding) -> &'static Encoding {
+	if let Some((encoding, _)) = Encoding::for_bom(prefix) {
+		return encoding;
+	}
+
+	prescan(prefix).unwrap_or(fallback)
+}
+
+/// The [prescan](https://html.spec.whatwg.org/multipage/parsing.html#prescan-a-byte-stream-to-determine-its-encoding) algorithm from the HTML standard, which looks for `<meta>` elements that declare the encoding.
+fn prescan(input: &[u8]) -> Option<&'static Encoding> {
+	let mut pos: usize = 0;
+
+	while pos < input.len() {
+		let rest = &input[pos..];
+
+		if rest.starts_with(b"<!--") {
+			// Skip to the `>` at the end of the comment. The `-->` may overlap the `<!--`, as in `<!-->`.
+			pos += 2 + find(&rest[2..], b"-->")? + 2;
+		}
+		else if starts_with_ignore_ascii_case(rest, b"<meta") && rest.get(5).is_some_and(|b| is_space(*b) || *b == b'/') {
+			pos += 5;
+
+			if let Some(encoding) = prescan_meta(input, &mut pos) {
+				return Some(encoding);
+			}
+		}
+		else if rest.len() >= 2 && (
+			rest[0] == b'<' && rest[1].is_ascii_alphabetic()
+			|| rest.starts_with(b"</") && rest.get(2).is_some_and(u8::is_ascii_alphabetic)
+		) {
+			// Skip the tag, including its attributes, which may contain `>` in quotes.
+			while pos < input.len() && !is_space(input[pos]) && input[pos] != b'>' {
+				pos += 1;
+			}
+
+			while get_attribute(input, &mut pos)?.is_some() {}
+		}
+		else if rest.starts_with(b"<!") || rest.starts_with(b"</") || rest.starts_with(b"<?") {
+			pos += find(rest, b">")?;
+		}
+
+		pos += 1;
+	}
+
+	None
+}
+
+/// Reads the attributes of a `<meta>` element, starting just after `<meta`, and returns the encoding it declares, if any.
+fn prescan_meta(input: &[u8], pos: &mut usize) -> Option<&'static Encoding> {
+	let mut attribute_names: Vec<Vec<u8>> = Vec::new();
+	let mut got_pragma = false;
+	let mut need_pragma: Option<bool> = None;
+	let mut charset: Option<&'static Encoding> = None;
+
+	while let Some((name, value)) = get_attribute(input, pos)? {
+		// Only the first attribute with a given name counts.
+		if attribute_names.contains(&name) {
+			continue;
+		}
+
+		match name.as_slice() {
+			b"http-equiv" if value == b"content-type" => {
+				got_pragma = true;
+			}
+
+			b"content" if charset.is_none() => {
+				if let Some(encoding) = charset_from_content(value.as_slice()) {
+					charset = Some(encoding);
+					need_pragma = Some(true);
+				}
+			}
+
+			b"charset" if charset.is_none() => {
+				charset = Encoding::for_label(value.as_slice());
+				need_pragma = Some(false);
+			}
+
+			_ => {}
+		}
+
+		attribute_names.push(name);
+	}
+
+	match need_pragma {
+		None => return None,
+		Some(true) if !got_pragma => return None,
+		_ => {}
+	}
+
+	let charset = charset?;
+
+	// A page can't be UTF-16 if the `<meta>` element could be read as ASCII, and `x-user-defined` is really just a variant of `windows-1252`.
+	if charset == encoding_rs::UTF_16BE || charset == encoding_rs::UTF_16LE {
+		Some(encoding_rs::UTF_8)
+	}
+	else if charset == encoding_rs::X_USER_DEFINED {
+		Some(encoding_rs::WINDOWS_1252)
+	}
+	else {
+		Some(charset)
+	}
+}
+
+/// The [get an attribute](https://html.spec.whatwg.org/multipage/parsing.html#concept-get-attributes-when-sniffing) algorithm from the HTML standard. Attribute names and values are converted to lowercase.
+///
+/// The return value is `None` at the end of the input, `Some(None)` at the end of the tag, or `Some(Some(…))` for an attribute.
+fn get_attribute(input: &[u8], pos: &mut usize) -> Option<Option<(Vec<u8>, Vec<u8>)>> {
+	while is_space(*input.get(*pos)?) || input[*pos] == b'/' {
+		*pos += 1;
+	}
+
+	if input[*pos] == b'>' {
+		return Some(None);
+	}
+
+	let mut name: Vec<u8> = Vec::new();
+	let mut value: Vec<u8> = Vec::new();
+
+	// The attribute name.
+	loop {
+		let b = *input.get(*pos)?;
+
+		if b == b'=' && !name.is_empty() {
+			break;
+		}
+		else if is_space(b) {
+			// There may be spaces before the `=`, or there may be no value at all.
+			while is_space(*input.get(*pos)?) {
+				*pos += 1;
+			}
+
+			if input[*pos] != b'=' {
+				return Some(Some((name, value)));
+			}
+
+			break;
+		}
+		else if b == b'/' || b == b'>' {
+			return Some(Some((name, value)));
+		}
+
+		name.push(b.to_ascii_lowercase());
+		*pos += 1;
+	}
+
+	// Skip the `=` and any spaces after it.
+	*pos += 1;
+
+	while is_space(*input.get(*pos)?) {
+		*pos += 1;
+	}
+
+	// The attribute value.
+	let b = input[*pos];
+
+	if b == b'"' || b == b'\'' {
+		let quote = b;
+		*pos += 1;
+
+		loop {
+			let b = *input.get(*pos)?;
+			*pos += 1;
+
+			if b == quote {
+				return Some(Some((name, value)));
+			}
+
+			value.push(b.to_ascii_lowercase());
+		}
+	}
+	else if b == b'>' {
+		return Some(Some((name, value)));
+	}
+
+	loop {
+		let b = *input.get(*pos)?;
+
+		if is_space(b) || b == b'>' {
+			return Some(Some((name, value)));
+		}
+
+		value.push(b.to_ascii_lowercase());
+		*pos += 1;
+	}
+}
+
+/// The [algorithm for extracting a character encoding from a `meta` element](https://html.spec.whatwg.org/multipage/urls-and-fetching.html#algorithm-for-extracting-a-character-encoding-from-a-meta-element), which finds the `charset` in a `content` attribute like `text/html; charset=shift_jis`.
+fn charset_from_content(content: &[u8]) -> Option<&'static Encoding> {
+	let mut pos: usize = 0;
+
+	loop {
+		pos += find_ignore_ascii_case(&content[pos..], b"charset")? + b"charset".len();
+
+		while content.get(pos).is_some_and(|b| is_space(*b)) {
+			pos += 1;
+		}
+
+		if content.get(pos) == Some(&b'=') {
+			pos += 1;
+			break;
+		}
+	}
+
+	while content.get(pos).is_some_and(|b| is_space(*b)) {
+		pos += 1;
+	}
+
+	let rest = &content[pos..];
+
+	let label: &[u8] = match rest.first()? {
+		quote @ (b'"' | b'\'') => {
+			let end = rest[1..].iter().position(|b| b == quote)?;
+			&rest[1..end + 1]
+		}
+
+		_ => {
+			let end = rest.iter().position(|b| is_space(*b) || *b == b';').unwrap_or(rest.len());
+			&rest[..end]
+		}
+	};
+
+	Encoding::for_label(label)
+}
+
+/// Whitespace, as defined by the HTML standard.
+fn is_space(b: u8) -> bool {
+	matches!(b, b'\t' | b'\n' | b'\x0C' | b'\r' | b' ')
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+	haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn find_ignore_ascii_case(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+	haystack.windows(needle.len()).position(|window| window.eq_ignore_ascii_case(needle))
+}
+
+fn starts_with_ignore_ascii_case(haystack: &[u8], prefix: &[u8]) -> bool {
+	haystack.get(..prefix.len()).is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+}
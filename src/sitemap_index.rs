@@ -0,0 +1,67 @@
+use anyhow::Context as _;
+use chrono::{DateTime, FixedOffset};
+use crate::{
+	config::Config,
+	MAX_SITEMAP_URLS,
+	scan::{file_time, Page},
+};
+use sitemap::{
+	structs::SiteMapEntry,
+	writer::SiteMapWriter,
+};
+use std::fs;
+use url::Url;
+
+/// Generates a sitemap index, listing the generated sitemap at `sitemap_url` followed by the configured [`Config::index_sitemaps`].
+///
+/// The `<lastmod>` of the generated sitemap is the latest `<lastmod>` of the `pages` in it. The `<lastmod>` of each other sitemap is the last modification time of its `lastmod_path`, if it has one.
+pub fn sitemap_index(cfg: &Config, sitemap_url: &Url, pages: &[Page]) -> anyhow::Result<Vec<u8>> {
+	anyhow::ensure!(
+		cfg.index_sitemaps.len() < MAX_SITEMAP_URLS,
+		"more than {MAX_SITEMAP_URLS} sitemaps are to be included in the sitemap index, which is not allowed by the sitemaps protocol",
+	);
+
+	let mut sitemap_index = Vec::<u8>::new();
+	let mut sitemap_index_writer =
+		SiteMapWriter::new(&mut sitemap_index)
+		.start_sitemapindex()
+		.context("couldn't write sitemap index")?;
+
+	sitemap_index_writer.sitemap(entry(
+		sitemap_url,
+		pages.iter().filter_map(|page| page.lastmod).max(),
+	)?)
+	.context("couldn't write sitemap index entry")?;
+
+	for sitemap in &cfg.index_sitemaps {
+		let lastmod: Option<DateTime<FixedOffset>> = match &sitemap.lastmod_path {
+			Some(path) => Some(
+				fs::metadata(path)
+				.and_then(|md| md.modified())
+				.map(|t| file_time(t).into())
+				.with_context(|| format!("couldn't get the last modification time of `{}`, for sitemap `{}` in the sitemap index", path.display(), sitemap.url))?
+			),
+
+			None => None,
+		};
+
+		sitemap_index_writer.sitemap(entry(&sitemap.url, lastmod)?)
+		.context("couldn't write sitemap index entry")?;
+	}
+
+	sitemap_index_writer.end()
+	.context("couldn't write sitemap index")?;
+
+	Ok(sitemap_index)
+}
+
+fn entry(url: &Url, lastmod: Option<DateTime<FixedOffset>>) -> anyhow::Result<SiteMapEntry> {
+	let mut entry = SiteMapEntry::builder().loc(url.as_str());
+
+	if let Some(lastmod) = lastmod {
+		entry = entry.lastmod(lastmod);
+	}
+
+	entry.build()
+	.with_context(|| format!("couldn't make a sitemap index entry for `{url}`"))
+}
@@ -0,0 +1,131 @@
+use anyhow::Context as _;
+use crate::{
+	cmd::OutputTo,
+	config::Config,
+};
+use std::{
+	borrow::Cow,
+	fs,
+	io,
+	path::Path,
+};
+use url::Url;
+
+/// Figures out the URL that the sitemap will be published at: either the configured [`Config::sitemap_url`], or the URL corresponding to the file that the sitemap was written to.
+pub fn sitemap_url(cfg: &Config, output: &OutputTo) -> anyhow::Result<Url> {
+	if let Some(sitemap_url) = &cfg.sitemap_url {
+		return Ok(sitemap_url.clone());
+	}
+
+	let sitemap_path: &Path = match output {
+		OutputTo::File(path) => path,
+		OutputTo::Stdout => anyhow::bail!("can't figure out the URL of the sitemap because it's being written to standard output; please set `sitemap_url` in the configuration file"),
+	};
+
+	file_url(cfg, sitemap_path, "sitemap", "sitemap_url")
+}
+
+/// Figures out the URL that the sitemap index will be published at: either the configured [`Config::sitemap_index_url`], or the URL corresponding to the file that the sitemap index was written to.
+pub fn sitemap_index_url(cfg: &Config, sitemap_index_path: &Path) -> anyhow::Result<Url> {
+	if let Some(sitemap_index_url) = &cfg.sitemap_index_url {
+		return Ok(sitemap_index_url.clone());
+	}
+
+	file_url(cfg, sitemap_index_path, "sitemap index", "sitemap_index_url")
+}
+
+/// Figures out the URL of a file inside the `root_dir`. `what` and `setting` are used in the error message if it isn't inside the `root_dir`.
+fn file_url(cfg: &Config, path: &Path, what: &str, setting: &str) -> anyhow::Result<Url> {
+	// The path may be relative to the current directory, if it was given with `-o`.
+	let path: Cow<Path> =
+		if path.is_absolute() {
+			Cow::Borrowed(path)
+		}
+		else {
+			Cow::Owned(
+				std::env::current_dir()
+				.context("couldn't get current working directory")?
+				.join(path)
+			)
+		};
+	let path = &*path;
+
+	let url_path: String =
+		crate::url_path::from_file_path(cfg.root_dir.as_path(), path)?
+		.with_context(|| format!(
+			"can't figure out the URL of the {what} because it's being written to `{}`, which is not inside the `root_dir`; please set `{setting}` in the configuration file",
+			path.display(),
+		))?;
+
+	cfg.root_url.join(url_path.as_str())
+	.with_context(|| format!("`{url_path}` is not a valid relative URL"))
+}
+
+/// Makes sure that the `robots.txt` file at `robots_path` has a `Sitemap:` line pointing to `sitemap_url`, creating the file if it doesn't exist.
+///
+/// If `robots.txt` already has such a line, it is left unchanged. Otherwise, the line is added at the end. The rest of the file is preserved, including any other `Sitemap:` lines, since they may be for sitemaps generated by other tools.
+pub fn update_robots_txt(robots_path: &Path, sitemap_url: &Url) -> anyhow::Result<()> {
+	let mut robots_txt: String = match fs::read(robots_path) {
+		Ok(bytes) => {
+			String::from_utf8(bytes)
+			.map_err(|_| anyhow::format_err!("`{}` is not valid UTF-8", robots_path.display()))?
+		}
+
+		Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+
+		Err(error) => return Err(
+			anyhow::Error::new(error)
+			.context(format!("couldn't read `robots.txt` at `{}`", robots_path.display()))
+		),
+	};
+
+	let already_present: bool =
+		robots_txt.lines()
+		.filter_map(sitemap_line_url)
+		.any(|url| url == sitemap_url.as_str());
+
+	if already_present {
+		return Ok(());
+	}
+
+	// Use the same line endings as the rest of the file.
+	let newline: &str =
+		if robots_txt.contains("\r\n") {
+			"\r\n"
+		}
+		else {
+			"\n"
+		};
+
+	if !robots_txt.is_empty() {
+		if !robots_txt.ends_with('\n') {
+			robots_txt.push_str(newline);
+		}
+
+		// Separate the `Sitemap:` line from the last group of rules, to make it clear that it isn't part of that group. If the file already ends with `Sitemap:` lines, put it with them instead.
+		let ends_with_sitemap_line: bool =
+			robots_txt.lines()
+			.last()
+			.and_then(sitemap_line_url)
+			.is_some();
+
+		if !ends_with_sitemap_line && !robots_txt.ends_with(newline.repeat(2).as_str()) {
+			robots_txt.push_str(newline);
+		}
+	}
+
+	robots_txt.push_str("Sitemap: ");
+	robots_txt.push_str(sitemap_url.as_str());
+	robots_txt.push_str(newline);
+
+	fs::write(robots_path, robots_txt)
+	.with_context(|| format!("couldn't write `robots.txt` at `{}`", robots_path.display()))
+}
+
+/// If the line is a `Sitemap:` line, returns the URL in it, without any comment or surrounding whitespace.
+fn sitemap_line_url(line: &str) -> Option<&str> {
+	let (key, value) = line.split_once(':')?;
+
+	key.trim().eq_ignore_ascii_case("sitemap")
+	.then(|| value.split('#').next().unwrap_or_default().trim())
+}
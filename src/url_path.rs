@@ -0,0 +1,119 @@
+use crate::config::UnicodeNormalization;
+use percent_encoding::{AsciiSet, CONTROLS};
+use std::{
+	borrow::Cow,
+	path::Path,
+};
+use url::Url;
+
+/// The set of characters that are percent-encoded in each segment of a URL-path. This is the same set that [`url::Url::from_file_path`] uses, so that decoding and then re-encoding a URL-path yields the same URL-path.
+const PATH_SEGMENT: &AsciiSet =
+	&CONTROLS
+	.add(b' ').add(b'"').add(b'<').add(b'>').add(b'`')
+	.add(b'#').add(b'?').add(b'{').add(b'}')
+	.add(b'/').add(b'%');
+
+/// Computes the URL-path of the file at `path`, relative to `root_dir`. Both paths must be absolute.
+///
+/// The return value is `None` if `path` is not inside `root_dir`.
+pub fn from_file_path(root_dir: &Path, path: &Path) -> anyhow::Result<Option<String>> {
+	if !path.starts_with(root_dir) {
+		return Ok(None);
+	}
+
+	let root_dir_url: Url =
+		Url::from_directory_path(root_dir)
+		.map_err(|()| anyhow::format_err!("`{}` is not a valid `root_dir`", root_dir.display()))?;
+
+	let file_url: Url =
+		Url::from_file_path(path)
+		.map_err(|()| anyhow::format_err!("path `{}` couldn't be converted into a URL", path.display()))?;
+
+	Ok(root_dir_url.make_relative(&file_url))
+}
+
+/// Decodes a percent-encoded URL-path. Byte sequences that aren't valid UTF-8 are replaced with U+FFFD REPLACEMENT CHARACTER.
+pub fn decode(url_path: &str) -> Cow<'_, str> {
+	percent_encoding::percent_decode_str(url_path)
+	.decode_utf8_lossy()
+}
+
+/// Decodes a percent-encoded URL-path, and replaces slashes with the platform's path separator. On Unix-like platforms, this is the same as [`decode`].
+pub fn decode_to_file_path(url_path: &str) -> Cow<'_, str> {
+	let decoded = decode(url_path);
+
+	if std::path::MAIN_SEPARATOR == '/' {
+		decoded
+	}
+	else {
+		Cow::Owned(decoded.replace('/', std::path::MAIN_SEPARATOR_STR))
+	}
+}
+
+/// Percent-encodes a decoded path, producing a URL-path. Slashes separate segments and are not encoded.
+pub fn encode(path: &str) -> String {
+	let mut encoded = String::with_capacity(path.len());
+
+	for (index, segment) in path.split('/').enumerate() {
+		if index != 0 {
+			encoded.push('/');
+		}
+
+		encoded.extend(percent_encoding::utf8_percent_encode(segment, PATH_SEGMENT));
+	}
+
+	encoded
+}
+
+/// Percent-encodes a relative file path that uses the platform's path separator. This is the inverse of [`decode_to_file_path`].
+pub fn encode_file_path(path: &str) -> String {
+	if std::path::MAIN_SEPARATOR == '/' {
+		encode(path)
+	}
+	else {
+		encode(path.replace(std::path::MAIN_SEPARATOR, "/").as_str())
+	}
+}
+
+/// Applies Unicode normalization to the characters encoded in a URL-path.
+///
+/// The result is [`Cow::Borrowed`] if normalization didn't change anything, including if the URL-path doesn't decode to valid UTF-8 (in which case it can't be normalized).
+pub fn normalize(url_path: &str, form: UnicodeNormalization) -> Cow<'_, str> {
+	let decoded = match percent_encoding::percent_decode_str(url_path).decode_utf8() {
+		Ok(ok) => ok,
+		Err(_) => return Cow::Borrowed(url_path),
+	};
+
+	match normalize_str(&decoded, form) {
+		Cow::Borrowed(_) => Cow::Borrowed(url_path),
+		Cow::Owned(normalized) => Cow::Owned(encode(normalized.as_str())),
+	}
+}
+
+/// Applies Unicode normalization to a string.
+///
+/// The result is [`Cow::Borrowed`] if normalization didn't change anything.
+pub fn normalize_str(s: &str, form: UnicodeNormalization) -> Cow<'_, str> {
+	use unicode_normalization::{IsNormalized, UnicodeNormalization as _};
+
+	let quick_check = match form {
+		UnicodeNormalization::Nfc => unicode_normalization::is_nfc_quick(s.chars()),
+		UnicodeNormalization::Nfd => unicode_normalization::is_nfd_quick(s.chars()),
+	};
+
+	if quick_check == IsNormalized::Yes {
+		return Cow::Borrowed(s);
+	}
+
+	let normalized: String = match form {
+		UnicodeNormalization::Nfc => s.nfc().collect(),
+		UnicodeNormalization::Nfd => s.nfd().collect(),
+	};
+
+	if normalized == s {
+		Cow::Borrowed(s)
+	}
+	else {
+		Cow::Owned(normalized)
+	}
+}
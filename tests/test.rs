@@ -1,8 +1,8 @@
 use snapbox::path::PathFixture;
 use std::path::{Path, PathBuf};
 
-#[test]
-fn test() {
+/// Makes a temporary copy of the `test-data` folder, with consistent file times.
+fn test_data() -> PathFixture {
 	let test_data: PathFixture =
 		PathFixture::mutable_temp()
 		.unwrap()
@@ -12,11 +12,35 @@ fn test() {
 
 	// Set file times to a consistent value.
 	for (f, t) in [
+		(&["content", "contact.md"][..], 2468),
+		(&["merge-shop-sitemap.xml"][..], 3000),
+		(&["site", "404.html"][..], 404),
+		(&["site", "about"][..], 77),
+		(&["site", "cafe\u{301}.HTML"][..], 33),
+		(&["site", "directives", "canonical-elsewhere.xhtml"][..], 98),
+		(&["site", "directives", "entity-title.xhtml"][..], 97),
+		(&["site", "directives", "other-namespace.xhtml"][..], 99),
+		(&["site", "directives", "unexpired.html"][..], 88),
+		(&["site", "docs", "docs.html"][..], 123),
+		(&["site", "errors", "letters.html"][..], 202),
+		(&["site", "errors", "missing.html"][..], 405),
+		(&["site", "errors", "nginx.html"][..], 203),
+		(&["site", "errors", "offline-first.html"][..], 204),
+		(&["site", "errors", "ok-status.html"][..], 200),
+		(&["site", "errors", "summer.html"][..], 501),
+		(&["site", "errors", "unknown-status.html"][..], 201),
+		(&["site", "errors", "you-are-offline.html"][..], 406),
 		(&["site", "foo", "bar.html"][..], 101),
 		(&["site", "index.html"][..], 42),
 		(&["site", "index.txt"][..], 54),
+		(&["site", "moved", "in-body.html"][..], 67),
+		(&["site", "moved", "not-a-redirect.html"][..], 21),
+		(&["site", "moved", "old-about.html"][..], 66),
+		(&["site", "pdfs", "broken.pdf"][..], 1790),
+		(&["site", "pdfs", "public.pdf"][..], 1789),
 		(&["site", "secret", "secrets.html"][..], 0xdeadbeef),
 		(&["site", "super-secret", "more-secrets.html"][..], 0xdeadbeef),
+		(&["site", "über", "straße.html"][..], 11),
 	] {
 		let f = PathBuf::from_iter(
 			[test_data_path].into_iter()
@@ -31,13 +55,424 @@ fn test() {
 		.unwrap_or_else(|error| panic!("couldn't set file time on `{}`: {error}", f.display()));
 	}
 
+	test_data
+}
+
+/// Runs `sitemap-from-files` on a copy of the `test-data` folder, using the configuration file `config` in it, and checks that it succeeds without complaint and writes the sitemap in `test-data/{expected}` to standard output.
+fn check(config: &str, expected: &str) {
+	check_with(config, expected, |cmd, _| cmd);
+}
+
+/// Same as [`check`], but `customize` can add command-line options or standard input. It's given the path to the copy of the `test-data` folder.
+fn check_with(config: &str, expected: &str, customize: impl FnOnce(snapbox::cmd::Command, &Path) -> snapbox::cmd::Command) {
+	let test_data = test_data();
+	let test_data_path: &Path = test_data.path().unwrap();
+
+	customize(snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("sitemap-from-files")), test_data_path)
+	.arg(test_data_path.join(config))
+	.assert()
+	.success()
+	.stderr_eq("")
+	.stdout_eq_path(PathBuf::from_iter([env!("CARGO_MANIFEST_DIR"), "test-data", expected]));
+
+	test_data.close().unwrap();
+}
+
+#[test]
+fn test() {
+	check_with("config.toml", "expected-sitemap.xml", |cmd, _| {
+		cmd
+		.args(["-o", "-"])
+	});
+}
+
+#[test]
+fn replace_mode_chain() {
+	check("config-chain.toml", "expected-sitemap-chain.xml");
+}
+
+#[test]
+fn match_normalization() {
+	check("config-normalization.toml", "expected-sitemap-normalization.xml");
+}
+
+#[test]
+fn match_normalization_replace() {
+	check("config-normalization-replace.toml", "expected-sitemap-normalization-replace.xml");
+}
+
+#[test]
+fn robots_user_agents() {
+	check("config-robots-agents.toml", "expected-sitemap-robots-agents.xml");
+}
+
+#[test]
+fn headers_file() {
+	// The `_headers` file in the `root_dir` is used even though the configuration doesn't name it.
+	check_with("config-headers.toml", "expected-sitemap-headers.xml", |cmd, _| {
+		cmd
+		.arg("--file-list")
+		.arg("-")
+		.stdin("index.html\ndrafts/draft.html\nfoo/bar.html\n")
+	});
+
+	// A line that isn't recognized, like Cloudflare Pages' `! X-Frame-Options`, is skipped, with a warning in verbose mode.
+	let test_data = test_data();
+	let test_data_path: &Path = test_data.path().unwrap();
+
+	let output =
+		snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("sitemap-from-files"))
+		.arg("--verbose")
+		.arg("--file-list")
+		.arg("-")
+		.arg(test_data_path.join("config-headers.toml"))
+		.stdin("index.html\n")
+		.assert()
+		.success()
+		.get_output()
+		.clone();
+
+	let stderr = String::from_utf8(output.stderr).unwrap();
+
+	assert!(
+		stderr.lines().any(|line| line.starts_with("Warning: line 7 of headers file ") && line.contains("`! X-Frame-Options`")),
+		"expected a warning about line 7 of the headers file, got:\n{stderr}",
+	);
+
+	test_data.close().unwrap();
+}
+
+#[test]
+fn meta_robots_agents() {
+	check("config-meta-agents.toml", "expected-sitemap-meta-agents.xml");
+}
+
+#[test]
+fn meta_robots_position() {
+	check("config-meta-position.toml", "expected-sitemap-meta-position.xml");
+
+	let test_data = test_data();
+	let test_data_path: &Path = test_data.path().unwrap();
+
+	// With the default, strict position check, the `<meta name=robots>` in the `<body>` of `foo/bar.html` is ignored, with a warning.
+	let output =
+		snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("sitemap-from-files"))
+		.args(["--verbose", "-o", "-"])
+		.arg(test_data_path.join("config-meta-position-strict.toml"))
+		.assert()
+		.success()
+		.get_output()
+		.clone();
+
+	let stderr = String::from_utf8(output.stderr).unwrap();
+	assert!(
+		stderr.lines().any(|line| {
+			line.starts_with("Warning: `foo/bar.html` ")
+			&& line.ends_with(" has `<meta name=robots content=\"noindex\">`, but it was ignored because of where it is in the document; see the `meta_robots_position` setting.")
+		}),
+		"expected a warning about `foo/bar.html`, got:\n{stderr}",
+	);
+
+	test_data.close().unwrap();
+}
+
+#[test]
+fn max_html_read_bytes() {
+	check("config-html-read-limit.toml", "expected-sitemap-html-read-limit.xml");
+}
+
+#[test]
+fn xhtml() {
+	check("config-xhtml.toml", "expected-sitemap-xhtml.xml");
+
+	let test_data = test_data();
+	let test_data_path: &Path = test_data.path().unwrap();
+
+	// A canonical link pointing elsewhere gets a warning. One pointing to the page itself doesn't.
+	let output =
+		snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("sitemap-from-files"))
+		.arg("--verbose")
+		.arg(test_data_path.join("config-xhtml.toml"))
+		.assert()
+		.success()
+		.get_output()
+		.clone();
+
+	let stderr = String::from_utf8(output.stderr).unwrap();
+	let canonical_warnings: Vec<&str> =
+		stderr.lines()
+		.filter(|line| line.starts_with("Warning: ") && line.contains("<link rel=canonical"))
+		.collect();
+
+	assert!(
+		canonical_warnings.len() == 1 && canonical_warnings[0].starts_with("Warning: `directives/canonical-elsewhere.xhtml` "),
+		"expected a warning about `directives/canonical-elsewhere.xhtml` only, got:\n{stderr}",
+	);
+
+	// HTML's named character references in titles are understood.
+	snapbox::assert_eq_path(
+		PathBuf::from_iter([env!("CARGO_MANIFEST_DIR"), "test-data", "expected-html-sitemap-xhtml.html"]),
+		std::fs::read_to_string(test_data_path.join("sitemap.html")).unwrap(),
+	);
+
+	test_data.close().unwrap();
+}
+
+#[test]
+fn html_redirect_replace() {
+	let test_data = test_data();
+	let test_data_path: &Path = test_data.path().unwrap();
+
 	snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("sitemap-from-files"))
-	.args(["-o", "-"])
-	.arg(test_data_path.join("config.toml"))
+	.arg(test_data_path.join("config-redirects.toml"))
 	.assert()
 	.success()
 	.stderr_eq("")
-	.stdout_eq_path(PathBuf::from_iter([env!("CARGO_MANIFEST_DIR"), "test-data", "expected-sitemap.xml"]));
+	.stdout_eq_path(PathBuf::from_iter([env!("CARGO_MANIFEST_DIR"), "test-data", "expected-sitemap-redirects.xml"]));
+
+	// The redirecting pages' titles, like "Redirecting…", aren't used for their targets. `moved/in-body.html` is found to redirect even though its `<meta http-equiv=refresh>` is in the `<body>`.
+	snapbox::assert_eq_path(
+		PathBuf::from_iter([env!("CARGO_MANIFEST_DIR"), "test-data", "expected-html-sitemap-redirects.html"]),
+		std::fs::read_to_string(test_data_path.join("sitemap.html")).unwrap(),
+	);
+
+	test_data.close().unwrap();
+}
+
+#[test]
+fn error_pages() {
+	// With the built-in lists of error page file names and titles turned off, only the page with `<meta name=prerender-status-code content=404>` is excluded.
+	check("config-error-pages.toml", "expected-sitemap-error-pages.xml");
+}
+
+#[test]
+fn html_sitemap() {
+	let test_data = test_data();
+	let test_data_path: &Path = test_data.path().unwrap();
+
+	snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("sitemap-from-files"))
+	.arg("-o")
+	.arg(test_data_path.join("sitemap.xml"))
+	.arg(test_data_path.join("config-html-sitemap.toml"))
+	.assert()
+	.success()
+	.stderr_eq("")
+	.stdout_eq("");
+
+	snapbox::assert_eq_path(
+		PathBuf::from_iter([env!("CARGO_MANIFEST_DIR"), "test-data", "expected-html-sitemap.html"]),
+		std::fs::read_to_string(test_data_path.join("sitemap.html")).unwrap(),
+	);
+
+	test_data.close().unwrap();
+}
+
+#[test]
+fn html_encoding() {
+	let test_data = test_data();
+	let test_data_path: &Path = test_data.path().unwrap();
+
+	// The titles in the HTML sitemap are only right if each page's character encoding is detected correctly: from a `<meta>` element, or else `html_fallback_encoding`.
+	snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("sitemap-from-files"))
+	.arg("-o")
+	.arg(test_data_path.join("sitemap.xml"))
+	.arg(test_data_path.join("config-encoding.toml"))
+	.assert()
+	.success()
+	.stderr_eq("")
+	.stdout_eq("");
+
+	snapbox::assert_eq_path(
+		PathBuf::from_iter([env!("CARGO_MANIFEST_DIR"), "test-data", "expected-html-sitemap-encoding.html"]),
+		std::fs::read_to_string(test_data_path.join("sitemap.html")).unwrap(),
+	);
+
+	test_data.close().unwrap();
+}
+
+#[test]
+fn html_encoding_utf_8() {
+	let test_data = test_data();
+	let test_data_path: &Path = test_data.path().unwrap();
+
+	// Without `html_fallback_encoding`, a page that doesn't declare its encoding is read as UTF-8.
+	snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("sitemap-from-files"))
+	.arg("-o")
+	.arg(test_data_path.join("sitemap.xml"))
+	.arg(test_data_path.join("config-encoding-utf-8.toml"))
+	.assert()
+	.success()
+	.stderr_eq("")
+	.stdout_eq("");
+
+	snapbox::assert_eq_path(
+		PathBuf::from_iter([env!("CARGO_MANIFEST_DIR"), "test-data", "expected-html-sitemap-encoding-utf-8.html"]),
+		std::fs::read_to_string(test_data_path.join("sitemap.html")).unwrap(),
+	);
+
+	test_data.close().unwrap();
+}
+
+#[test]
+fn pdf_noindex() {
+	let test_data = test_data();
+	let test_data_path: &Path = test_data.path().unwrap();
+
+	// A PDF document that can't be read is included, with a warning.
+	snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("sitemap-from-files"))
+	.arg(test_data_path.join("config-pdf.toml"))
+	.assert()
+	.success()
+	.stderr_matches("\
+Warning: `pdfs/broken.pdf` (at file path `[..]`) looks like a PDF document, but it couldn't be read as one, so its metadata wasn't checked: [..]
+")
+	.stdout_eq_path(PathBuf::from_iter([env!("CARGO_MANIFEST_DIR"), "test-data", "expected-sitemap-pdf.xml"]));
+
+	test_data.close().unwrap();
+}
+
+#[test]
+fn front_matter() {
+	check("config-front-matter.toml", "expected-sitemap-front-matter.xml");
+}
+
+#[test]
+fn file_list() {
+	check_with("config-file-list.toml", "expected-sitemap-file-list.xml", |cmd, _| {
+		cmd
+		.arg("--file-list")
+		.arg("-")
+		.stdin("index.html\n./foo/bar.html\nmeta_robots.html\n\ngenerated/not-on-disk.html\r\nsomething.else\n")
+	});
+}
+
+#[test]
+fn file_list_json() {
+	check("config-file-list-json.toml", "expected-sitemap-file-list-json.xml");
+}
+
+#[test]
+fn archive() {
+	check("config-archive.toml", "expected-sitemap-archive.xml");
+
+	check_with("config-archive.toml", "expected-sitemap-archive-zip.xml", |cmd, test_data_path| {
+		cmd
+		.arg("--archive")
+		.arg(test_data_path.join("site.zip"))
+	});
+
+	// The same files, in a `site` folder in the archive. `robots.txt` is found there too, so `private/secret.html` is still excluded.
+	check("config-archive-strip-prefix.toml", "expected-sitemap-archive.xml");
+}
+
+#[test]
+fn merge_sitemap() {
+	check_with("config-merge.toml", "expected-sitemap-merge.xml", |cmd, _| {
+		cmd
+		.arg("--file-list")
+		.arg("-")
+		.stdin("index.html\nfoo/bar.html\n")
+	});
+}
+
+#[test]
+fn sitemap_index() {
+	let test_data = test_data();
+	let test_data_path: &Path = test_data.path().unwrap();
+
+	snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("sitemap-from-files"))
+	.arg("--file-list")
+	.arg("-")
+	.arg(test_data_path.join("config-sitemap-index.toml"))
+	.stdin("index.html\nfoo/bar.html\n")
+	.assert()
+	.success()
+	.stderr_eq("")
+	.stdout_eq("");
+
+	snapbox::assert_eq_path(
+		PathBuf::from_iter([env!("CARGO_MANIFEST_DIR"), "test-data", "expected-sitemap-index.xml"]),
+		std::fs::read_to_string(test_data_path.join("site").join("sitemap_index.xml")).unwrap(),
+	);
+
+	// `robots.txt` points to the sitemap index, not the sitemap.
+	snapbox::assert_eq_path(
+		PathBuf::from_iter([env!("CARGO_MANIFEST_DIR"), "test-data", "expected-robots-index.txt"]),
+		std::fs::read_to_string(test_data_path.join("site").join("robots.txt")).unwrap(),
+	);
+
+	test_data.close().unwrap();
+}
+
+#[test]
+fn robots_txt_path() {
+	check("config-robots-path.toml", "expected-sitemap-robots-path.xml");
+}
+
+#[test]
+fn update_robots_txt() {
+	let test_data = test_data();
+	let test_data_path: &Path = test_data.path().unwrap();
+
+	// Run it twice. The second run should leave `robots.txt` unchanged, rather than adding a second `Sitemap:` line.
+	for _ in 0..2 {
+		snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("sitemap-from-files"))
+		.arg(test_data_path.join("config-update-robots.toml"))
+		.assert()
+		.success()
+		.stderr_eq("")
+		.stdout_eq("");
+
+		snapbox::assert_eq_path(
+			PathBuf::from_iter([env!("CARGO_MANIFEST_DIR"), "test-data", "expected-robots-updated.txt"]),
+			std::fs::read_to_string(test_data_path.join("site").join("robots.txt")).unwrap(),
+		);
+	}
+
+	test_data.close().unwrap();
+}
+
+#[test]
+fn update_robots_txt_other_sitemaps() {
+	let test_data = test_data();
+	let test_data_path: &Path = test_data.path().unwrap();
+	let robots_txt_path: PathBuf = test_data_path.join("site").join("robots.txt");
+
+	// `Sitemap:` lines for other sitemaps, on the same site or another one, are left alone. The new line goes after them, with the same line endings.
+	std::fs::write(
+		robots_txt_path.as_path(),
+		"User-Agent: *\r\nDisallow: /super-secret/\r\n\r\nSitemap: https://www.example.com/news-sitemap.xml # generated by the CMS\r\nSitemap: https://www.example.com/shop/sitemap.xml\r\nSitemap: https://blog.example.com/sitemap.xml\r\n",
+	).unwrap();
+
+	snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("sitemap-from-files"))
+	.arg(test_data_path.join("config-update-robots.toml"))
+	.assert()
+	.success()
+	.stderr_eq("")
+	.stdout_eq("");
+
+	assert_eq!(
+		std::fs::read_to_string(robots_txt_path.as_path()).unwrap(),
+		"User-Agent: *\r\nDisallow: /super-secret/\r\n\r\nSitemap: https://www.example.com/news-sitemap.xml # generated by the CMS\r\nSitemap: https://www.example.com/shop/sitemap.xml\r\nSitemap: https://blog.example.com/sitemap.xml\r\nSitemap: https://www.example.com/sitemap.xml\r\n",
+	);
+
+	test_data.close().unwrap();
+}
+
+#[test]
+fn robots_txt_lint() {
+	let test_data = test_data();
+	let test_data_path: &Path = test_data.path().unwrap();
+
+	snapbox::cmd::Command::new(snapbox::cmd::cargo_bin!("sitemap-from-files"))
+	.arg(test_data_path.join("config-robots-lint.toml"))
+	.assert()
+	.failure()
+	.stderr_matches("\
+Warning: `super-secret/more-secrets.html` (at file path `[..]`) is included by the rules, but `robots.txt` says to exclude it for user-agent `*` (`Disallow: /super-secret/`).
+Error: `robots.txt` excluded 1 of the 3 files that the rules say to include (33.3%), which is more than the configured `max_robots_txt_exclusion_percent` of 25%; please check `robots.txt` for mistakes (such as `Disallow: /`)
+")
+	.stdout_eq("");
 
 	test_data.close().unwrap();
 }